@@ -1,23 +1,103 @@
-use crate::state::SessionMode;
+use crate::state::{
+    AnalysisStatus, JournalSession, ResurfacedEntry, SessionMode, Speaker, TranscriptSummary,
+};
+use crate::tags;
+use std::io::{self, BufRead};
+use std::path::PathBuf;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Action {
     Start,
     Resume(Uuid),
+    /// A session doc read back for resuming
+    SessionLoaded(JournalSession),
     SelectMode(SessionMode),
     UserResponse(String),
     CoachResponse(String),
+    TranscriptSummarized(TranscriptSummary),
+    SessionSaved(Uuid),
     NextQuestion,
+    MakePrivate,
+    /// Past entries the coach may refer back to during this session
+    ShareResurfaced(Vec<ResurfacedEntry>),
     Stop,
+    /// Save the session and exit, leaving it active to continue later
+    Pause,
+    /// End the session without writing an entry, keeping the session doc
+    Abandon,
+    /// End the session and delete its doc; confirmed with the user first
+    Discard,
+    /// Take back the last response along with the coach's reply to it
+    Undo,
+    /// Ask the coach again for a reply to the last response
+    Retry,
+    /// Move on to another question without answering
+    Skip,
+    /// Tag the session, e.g. "work"
+    Tag(Vec<String>),
+    /// Star the latest line said by the user or the coach
+    Star(Speaker),
+    /// Switch between morning and evening; `None` switches to the other mode
+    SwitchMode(Option<SessionMode>),
+    /// Write the next response in the user's editor, or revise the last one there
+    ComposeInEditor {
+        revise_last: bool,
+    },
+    /// Replace the last response, keeping the old version, and get a new reply to it
+    ReviseLast(String),
+    Help,
+    /// A slash command that isn't recognized, kept as typed
+    UnknownCommand(String),
     AnalysisComplete(String),
+    /// The AI analysis could not be generated; carries the error for display
+    AnalysisFailed(String),
     FinalEntryCreated {
-        entry_path: String,
+        entry_id: Uuid,
+        entry_path: PathBuf,
         analysis: String,
+        analysis_status: AnalysisStatus,
+        /// Tags the user might want to add to the entry
+        suggested_tags: Vec<String>,
     },
 }
 
-impl Action {}
+impl Action {
+    /// Bookkeeping actions that update state without anything new to show the user
+    pub fn is_internal(&self) -> bool {
+        matches!(
+            self,
+            Action::TranscriptSummarized(_) | Action::SessionSaved(_) | Action::ShareResurfaced(_)
+        )
+    }
+}
+
+/// Slash commands available during a session, with what they do
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("/stop", "End the session and write the entry"),
+    ("/pause", "Save and exit, to continue later"),
+    (
+        "/abandon",
+        "End without writing an entry, keeping the transcript",
+    ),
+    ("/discard", "End and delete this session"),
+    ("/undo", "Take back your last response"),
+    ("/retry", "Ask the coach for a different reply"),
+    (
+        "/revise [text]",
+        "Rewrite your last response, in $EDITOR if no text is given",
+    ),
+    ("/skip", "Move on to another question"),
+    ("/tag <tags>", "Tag this session, e.g. /tag work"),
+    (
+        "/star [coach]",
+        "Star your last response, or the coach's last line",
+    ),
+    ("/mode [morning|evening]", "Switch session mode"),
+    ("/private", "Keep the rest of the session local-only"),
+    ("/edit", "Write your next response in $EDITOR"),
+    ("/help", "Show these commands"),
+];
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct UserInput {
@@ -40,6 +120,31 @@ impl UserInput {
         }
     }
 
+    /// Read one response from `reader`. During a session a response may span several lines
+    /// and ends at a blank line or end of input (Ctrl-D); a command typed on the first line is
+    /// submitted straight away.
+    pub fn read(reader: &mut impl BufRead, context: &InputContext) -> io::Result<String> {
+        let mut lines: Vec<String> = Vec::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            lines.push(line.trim_end_matches(['\r', '\n']).to_string());
+
+            let single_line = *context == InputContext::ModeSelection || Self::is_command(&line);
+            if lines.len() == 1 && single_line {
+                break;
+            }
+        }
+
+        Ok(lines.join("\n").trim().to_string())
+    }
+
+    fn is_command(line: &str) -> bool {
+        line.trim_start().starts_with('/')
+    }
+
     fn parse_input(input: &str, context: InputContext) -> Action {
         let trimmed = input.trim();
 
@@ -53,10 +158,39 @@ impl UserInput {
                 "evening" | "e" => Action::SelectMode(SessionMode::Evening),
                 _ => Action::UserResponse(trimmed.to_string()),
             },
-            InputContext::InSession => match trimmed.to_lowercase().as_str() {
-                "s" | "stop" => Action::Stop,
-                _ => Action::UserResponse(trimmed.to_string()),
-            },
+            // Plain text is always a response; only slash commands do anything else
+            InputContext::InSession if trimmed.starts_with('/') => Self::parse_command(trimmed),
+            InputContext::InSession => Action::UserResponse(trimmed.to_string()),
+        }
+    }
+
+    fn parse_command(command: &str) -> Action {
+        let (name, original) = command.split_once(' ').unwrap_or((command, ""));
+        let original = original.trim();
+        let argument = original.to_lowercase();
+
+        match (name.to_lowercase().as_str(), argument.as_str()) {
+            ("/stop", "") => Action::Stop,
+            ("/pause", "") => Action::Pause,
+            ("/abandon", "") => Action::Abandon,
+            ("/discard", "") => Action::Discard,
+            ("/undo", "") => Action::Undo,
+            ("/retry", "") => Action::Retry,
+            ("/skip", "") => Action::Skip,
+            ("/tag", tags) if !tags.is_empty() => {
+                Action::Tag(tags.split([' ', ',']).filter_map(tags::normalize).collect())
+            }
+            ("/star", "") => Action::Star(Speaker::User),
+            ("/star", "coach") => Action::Star(Speaker::Coach),
+            ("/mode", "") => Action::SwitchMode(None),
+            ("/mode", "morning" | "m") => Action::SwitchMode(Some(SessionMode::Morning)),
+            ("/mode", "evening" | "e") => Action::SwitchMode(Some(SessionMode::Evening)),
+            ("/private", "") => Action::MakePrivate,
+            ("/revise", "") => Action::ComposeInEditor { revise_last: true },
+            ("/revise", _) => Action::ReviseLast(original.to_string()),
+            ("/edit", "") => Action::ComposeInEditor { revise_last: false },
+            ("/help", "") => Action::Help,
+            _ => Action::UnknownCommand(command.to_string()),
         }
     }
 }
@@ -94,15 +228,73 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_read_multi_line_response() {
+        let mut input = io::Cursor::new("First line\nsecond line\n\nNext response\n");
+        assert_eq!(
+            UserInput::read(&mut input, &InputContext::InSession).unwrap(),
+            "First line\nsecond line"
+        );
+        // End of input (Ctrl-D) submits what was typed
+        assert_eq!(
+            UserInput::read(&mut input, &InputContext::InSession).unwrap(),
+            "Next response"
+        );
+        assert_eq!(
+            UserInput::read(&mut input, &InputContext::InSession).unwrap(),
+            ""
+        );
+
+        let mut commands = io::Cursor::new("/stop\n/edit\ne\nmore\n");
+        for expected in ["/stop", "/edit"] {
+            assert_eq!(
+                UserInput::read(&mut commands, &InputContext::InSession).unwrap(),
+                expected
+            );
+        }
+        assert_eq!(
+            UserInput::read(&mut commands, &InputContext::ModeSelection).unwrap(),
+            "e"
+        );
+    }
+
     #[test]
     fn test_user_input_parsing_in_session() {
         let cases = vec![
             ("", Action::NextQuestion),
             ("   ", Action::NextQuestion),
-            ("s", Action::Stop),
-            ("stop", Action::Stop),
-            ("S", Action::Stop), // Test case insensitive
-            ("STOP", Action::Stop),
+            ("/stop", Action::Stop),
+            ("/STOP", Action::Stop), // Test case insensitive
+            ("/pause", Action::Pause),
+            ("/abandon", Action::Abandon),
+            ("/discard", Action::Discard),
+            ("/undo", Action::Undo),
+            ("/retry", Action::Retry),
+            ("/skip", Action::Skip),
+            (
+                "/tag work #Health",
+                Action::Tag(vec!["work".to_string(), "health".to_string()]),
+            ),
+            ("/star", Action::Star(Speaker::User)),
+            ("/star coach", Action::Star(Speaker::Coach)),
+            ("/mode", Action::SwitchMode(None)),
+            (
+                "/mode morning",
+                Action::SwitchMode(Some(SessionMode::Morning)),
+            ),
+            ("/private", Action::MakePrivate),
+            ("/edit", Action::ComposeInEditor { revise_last: false }),
+            ("/revise", Action::ComposeInEditor { revise_last: true }),
+            (
+                "/revise A Better Answer",
+                Action::ReviseLast("A Better Answer".to_string()),
+            ),
+            ("/help", Action::Help),
+            ("/tag", Action::UnknownCommand("/tag".to_string())),
+            ("/dance", Action::UnknownCommand("/dance".to_string())),
+            // Plain text never ends the session
+            ("s", Action::UserResponse("s".to_string())),
+            ("stop", Action::UserResponse("stop".to_string())),
             ("e", Action::UserResponse("e".to_string())),
             ("evening", Action::UserResponse("evening".to_string())),
             ("morning", Action::UserResponse("morning".to_string())),
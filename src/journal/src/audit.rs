@@ -0,0 +1,346 @@
+use crate::backend::estimate_tokens;
+use crate::index;
+use crate::prompt::PromptKind;
+use crate::redact::{RedactionKind, RedactionMap};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Location of the outbound AI audit log, relative to the vault root
+pub const AUDIT_LOG_FILE: &str = ".aethel/audit/journal.audit.jsonl";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AuditDirection {
+    Sent,
+    Received,
+    /// The backend call failed without a response, so the prompt may never have left the
+    /// machine
+    Failed,
+}
+
+/// A placeholder that replaced personal information in a sent prompt. The original
+/// text is deliberately not recorded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AppliedRedaction {
+    pub kind: RedactionKind,
+    pub placeholder: String,
+}
+
+/// One line of the audit log. Every exchange with the backend produces a `Sent`
+/// record before the prompt leaves the machine and a `Received` record afterwards, or a
+/// `Failed` record if the backend couldn't be run, linked by `exchange_id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub exchange_id: Uuid,
+    pub timestamp: DateTime<Utc>,
+    pub session_id: Option<Uuid>,
+    pub direction: AuditDirection,
+    pub kind: PromptKind,
+    pub backend: String,
+    pub template_hash: String,
+    pub bytes: usize,
+    pub tokens: usize,
+    #[serde(default)]
+    pub redactions: Vec<AppliedRedaction>,
+    /// Exit code of the backend process, for `Received` records
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    /// Exactly what was sent or received, or why the backend call failed
+    pub content: String,
+}
+
+impl AuditRecord {
+    pub fn sent(
+        exchange_id: Uuid,
+        session_id: Option<Uuid>,
+        kind: PromptKind,
+        backend: &str,
+        template_hash: &str,
+        prompt: &str,
+        redactions: &RedactionMap,
+    ) -> Self {
+        Self {
+            exchange_id,
+            timestamp: Utc::now(),
+            session_id,
+            direction: AuditDirection::Sent,
+            kind,
+            backend: backend.to_string(),
+            template_hash: template_hash.to_string(),
+            bytes: prompt.len(),
+            tokens: estimate_tokens(prompt),
+            redactions: redactions
+                .redactions
+                .iter()
+                .map(|r| AppliedRedaction {
+                    kind: r.kind,
+                    placeholder: r.placeholder.clone(),
+                })
+                .collect(),
+            exit_code: None,
+            content: prompt.to_string(),
+        }
+    }
+
+    pub fn received(sent: &AuditRecord, response: &str, exit_code: Option<i32>) -> Self {
+        Self {
+            exchange_id: sent.exchange_id,
+            timestamp: Utc::now(),
+            session_id: sent.session_id,
+            direction: AuditDirection::Received,
+            kind: sent.kind,
+            backend: sent.backend.clone(),
+            template_hash: sent.template_hash.clone(),
+            bytes: response.len(),
+            tokens: estimate_tokens(response),
+            redactions: Vec::new(),
+            exit_code,
+            content: response.to_string(),
+        }
+    }
+
+    pub fn failed(sent: &AuditRecord, error: &str) -> Self {
+        Self {
+            exchange_id: sent.exchange_id,
+            timestamp: Utc::now(),
+            session_id: sent.session_id,
+            direction: AuditDirection::Failed,
+            kind: sent.kind,
+            backend: sent.backend.clone(),
+            template_hash: sent.template_hash.clone(),
+            bytes: 0,
+            tokens: 0,
+            redactions: Vec::new(),
+            exit_code: None,
+            content: error.to_string(),
+        }
+    }
+}
+
+/// Append-only JSON Lines log of everything sent to and received from the AI backend
+pub struct AuditLog {
+    path: PathBuf,
+}
+
+impl AuditLog {
+    pub fn new(vault_path: &Path) -> Self {
+        Self {
+            path: vault_path.join(AUDIT_LOG_FILE),
+        }
+    }
+
+    pub fn append(&self, record: &AuditRecord) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create audit log directory")?;
+        }
+
+        let mut line = serde_json::to_string(record).context("Failed to serialize audit record")?;
+        line.push('\n');
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .context("Failed to open audit log")?;
+        file.write_all(line.as_bytes())
+            .context("Failed to write audit record")?;
+        file.sync_data().context("Failed to sync audit log")?;
+
+        Ok(())
+    }
+
+    pub fn records(&self) -> Result<Vec<AuditRecord>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read_to_string(&self.path).context("Failed to read audit log")?;
+        content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                serde_json::from_str(line)
+                    .with_context(|| format!("Malformed audit record on line {}", i + 1))
+            })
+            .collect()
+    }
+
+    /// Remove records, either all of them or only those for one session.
+    /// Returns the number of records removed.
+    pub fn purge(&self, session_id: Option<Uuid>) -> Result<usize> {
+        let records = self.records()?;
+        let (removed, kept): (Vec<_>, Vec<_>) = records
+            .into_iter()
+            .partition(|record| session_id.is_none() || record.session_id == session_id);
+
+        if kept.is_empty() {
+            if self.path.exists() {
+                fs::remove_file(&self.path).context("Failed to remove audit log")?;
+            }
+        } else {
+            let mut content = String::new();
+            for record in &kept {
+                content.push_str(&serde_json::to_string(record)?);
+                content.push('\n');
+            }
+            index::write_atomic(&self.path, &content).context("Failed to rewrite audit log")?;
+        }
+
+        Ok(removed.len())
+    }
+}
+
+/// Render audit records for the `journal audit` command
+pub fn format_records(records: &[AuditRecord], show_content: bool) -> String {
+    if records.is_empty() {
+        return "No AI exchanges have been recorded.".to_string();
+    }
+
+    let mut output = String::new();
+    for record in records {
+        let direction = match record.direction {
+            AuditDirection::Sent => "→ sent",
+            AuditDirection::Received => "← received",
+            AuditDirection::Failed => "✗ failed",
+        };
+        let session = record
+            .session_id
+            .map(|id| id.to_string())
+            .unwrap_or_else(|| "-".to_string());
+
+        output.push_str(&format!(
+            "{} {:<10} {:?} via {} | {} bytes, ~{} tokens | template {} | session {}",
+            record.timestamp.format("%Y-%m-%d %H:%M:%S"),
+            direction,
+            record.kind,
+            record.backend,
+            record.bytes,
+            record.tokens,
+            record.template_hash,
+            session,
+        ));
+        if !record.redactions.is_empty() {
+            output.push_str(&format!(" | {} redactions", record.redactions.len()));
+        }
+        if let Some(code) = record.exit_code.filter(|code| *code != 0) {
+            output.push_str(&format!(" | exit code {code}"));
+        }
+        output.push('\n');
+
+        if show_content {
+            for line in record.content.lines() {
+                output.push_str(&format!("    {line}\n"));
+            }
+            output.push('\n');
+        }
+    }
+
+    output.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::redact::Redaction;
+    use tempfile::TempDir;
+
+    fn sent_record(session_id: Option<Uuid>) -> AuditRecord {
+        let redactions = RedactionMap {
+            redactions: vec![Redaction {
+                kind: RedactionKind::Person,
+                placeholder: "[Person A]".to_string(),
+                original: "Sarah".to_string(),
+            }],
+        };
+        AuditRecord::sent(
+            Uuid::new_v4(),
+            session_id,
+            PromptKind::Coach,
+            "claude",
+            "0123456789abcdef",
+            "I talked to [Person A]",
+            &redactions,
+        )
+    }
+
+    #[test]
+    fn test_sent_record_omits_original_text() {
+        let record = sent_record(None);
+        assert_eq!(record.bytes, "I talked to [Person A]".len());
+        assert_eq!(record.redactions.len(), 1);
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(!json.contains("Sarah"));
+    }
+
+    #[test]
+    fn test_append_and_read_records() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = AuditLog::new(temp_dir.path());
+        assert!(log.records().unwrap().is_empty());
+
+        let sent = sent_record(Some(Uuid::new_v4()));
+        let received = AuditRecord::received(&sent, "How did that feel?", Some(0));
+        log.append(&sent).unwrap();
+        log.append(&received).unwrap();
+
+        let records = log.records().unwrap();
+        assert_eq!(records, vec![sent.clone(), received]);
+        assert_eq!(records[1].exchange_id, sent.exchange_id);
+        assert_eq!(records[1].direction, AuditDirection::Received);
+    }
+
+    #[test]
+    fn test_purge_by_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let log = AuditLog::new(temp_dir.path());
+        let session_a = Uuid::new_v4();
+        let session_b = Uuid::new_v4();
+
+        log.append(&sent_record(Some(session_a))).unwrap();
+        log.append(&sent_record(Some(session_b))).unwrap();
+        log.append(&sent_record(Some(session_a))).unwrap();
+
+        assert_eq!(log.purge(Some(session_a)).unwrap(), 2);
+        let remaining = log.records().unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].session_id, Some(session_b));
+        assert!(!temp_dir
+            .path()
+            .join(format!("{AUDIT_LOG_FILE}.tmp"))
+            .exists());
+
+        assert_eq!(log.purge(None).unwrap(), 1);
+        assert!(log.records().unwrap().is_empty());
+        assert!(!temp_dir.path().join(AUDIT_LOG_FILE).exists());
+    }
+
+    #[test]
+    fn test_format_records() {
+        assert_eq!(
+            format_records(&[], false),
+            "No AI exchanges have been recorded."
+        );
+
+        let sent = sent_record(None);
+        let formatted = format_records(std::slice::from_ref(&sent), false);
+        assert!(formatted.contains("→ sent"));
+        assert!(formatted.contains("1 redactions"));
+        assert!(!formatted.contains("I talked to"));
+
+        let with_content = format_records(std::slice::from_ref(&sent), true);
+        assert!(with_content.contains("    I talked to [Person A]"));
+
+        let failed = AuditRecord::failed(&sent, "Failed to execute claude command");
+        assert_eq!(failed.exchange_id, sent.exchange_id);
+        assert_eq!(failed.bytes, 0);
+        let formatted = format_records(&[sent, failed], true);
+        assert!(formatted.contains("✗ failed"));
+        assert!(formatted.contains("    Failed to execute claude command"));
+    }
+}
@@ -0,0 +1,86 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+/// The AI backend used for coaching and analysis
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AiBackend {
+    #[default]
+    Claude,
+}
+
+impl AiBackend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            AiBackend::Claude => "claude",
+        }
+    }
+
+    /// Maximum number of estimated tokens we are willing to send in a single prompt
+    pub fn token_budget(&self) -> usize {
+        match self {
+            AiBackend::Claude => 32_000,
+        }
+    }
+
+    /// Run the backend CLI, passing the prompt via stdin rather than argv so that
+    /// long transcripts don't hit argument-length limits
+    pub fn invoke(&self, prompt: &str) -> Result<Output> {
+        let mut child = Command::new(self.name())
+            .arg("-p")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to execute {} command", self.name()))?;
+
+        // Write on a separate thread so a chatty child can't deadlock on a full stdout pipe
+        let mut stdin = child.stdin.take().context("Failed to open claude stdin")?;
+        let prompt = prompt.to_string();
+        let writer = std::thread::spawn(move || stdin.write_all(prompt.as_bytes()));
+
+        let output = child
+            .wait_with_output()
+            .context("Failed to wait for claude command")?;
+
+        let write_result = writer
+            .join()
+            .map_err(|_| anyhow::anyhow!("Failed to write prompt to claude stdin"))?;
+
+        // A failing child may close stdin early; its exit status is the more useful error
+        if output.status.success() {
+            write_result.context("Failed to write prompt to claude stdin")?;
+        }
+
+        Ok(output)
+    }
+}
+
+/// Rough token estimate (~4 characters per token) used for budgeting prompts
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abc"), 1);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        // Counts characters, not bytes
+        assert_eq!(estimate_tokens("ééééé"), 2);
+    }
+
+    #[test]
+    fn test_default_backend() {
+        let backend = AiBackend::default();
+        assert_eq!(backend, AiBackend::Claude);
+        assert_eq!(backend.name(), "claude");
+        assert!(backend.token_budget() > 0);
+    }
+}
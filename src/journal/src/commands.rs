@@ -0,0 +1,709 @@
+use crate::audit::{self, AuditLog};
+use crate::config::JournalConfig;
+use crate::digest::{self, DigestStats, Period, PeriodKind};
+use crate::doctor;
+use crate::effects::{Effect, EffectRunner};
+use crate::entries::{self, EntryFilter};
+use crate::highlights;
+use crate::mentions;
+use crate::pack;
+use crate::pending::PendingQueue;
+use crate::sessions;
+use crate::state::{SessionMode, SessionStatus, Speaker};
+use crate::stats::TrendStats;
+use crate::streak::StreakStatus;
+use crate::tags;
+use crate::upgrade;
+use aethel_core::read_doc;
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use std::path::Path;
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AuditOptions {
+    pub session_id: Option<Uuid>,
+    pub show_content: bool,
+    pub purge: bool,
+    pub assume_yes: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AnalyzeOptions {
+    pub retry_pending: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReanalyzeOptions {
+    pub entry_id: Option<Uuid>,
+    pub filter: EntryFilter,
+    pub assume_yes: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DigestOptions {
+    pub period: PeriodKind,
+    /// Any day in the period to review
+    pub date: NaiveDate,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DoctorOptions {
+    /// Repair what can be repaired instead of only reporting it
+    pub fix: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PeopleOptions {
+    /// Extract the mentions of every entry again before listing them
+    pub reindex: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MentionsOptions {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StarOptions {
+    pub entry_id: Uuid,
+    /// Line to star, counting from 1; without one the lines are listed
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HighlightsOptions {
+    pub filter: EntryFilter,
+}
+
+/// What `journal tags` should do
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum TagsCommand {
+    #[default]
+    List,
+    Add {
+        entry_id: Uuid,
+        tags: Vec<String>,
+    },
+    Remove {
+        entry_id: Uuid,
+        tags: Vec<String>,
+    },
+    Rename {
+        from: String,
+        to: String,
+    },
+    Merge {
+        tags: Vec<String>,
+        into: String,
+    },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct UpgradeOptions {
+    /// Report the migrations without running them
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SessionsOptions {
+    /// List archived sessions instead of ones that can be resumed
+    pub archived: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StatsOptions {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub json: bool,
+}
+
+/// `journal audit`: show or purge the outbound AI audit log
+pub fn run_audit(vault_path: &Path, options: &AuditOptions) -> Result<()> {
+    let log = AuditLog::new(vault_path);
+
+    if options.purge {
+        let scope = match options.session_id {
+            Some(session_id) => format!("for session {session_id}"),
+            None => "for all sessions".to_string(),
+        };
+        let confirmed = options.assume_yes
+            || dialoguer::Confirm::new()
+                .with_prompt(format!("Delete the AI audit log {scope}?"))
+                .default(false)
+                .interact()?;
+        if !confirmed {
+            println!("Audit log left unchanged.");
+            return Ok(());
+        }
+
+        let removed = log.purge(options.session_id)?;
+        println!("🗑️  Removed {removed} audit records {scope}.");
+        return Ok(());
+    }
+
+    let records: Vec<_> = log
+        .records()?
+        .into_iter()
+        .filter(|record| options.session_id.is_none() || record.session_id == options.session_id)
+        .collect();
+
+    println!("{}", audit::format_records(&records, options.show_content));
+    Ok(())
+}
+
+/// `journal analyze`: list analyses deferred while the AI backend was unavailable, or retry
+/// them with `--pending`
+pub async fn run_analyze(
+    vault_path: &Path,
+    options: &AnalyzeOptions,
+    local_only: bool,
+) -> Result<()> {
+    let queued = PendingQueue::new(vault_path).load()?;
+    if queued.is_empty() {
+        println!("No AI analyses are pending.");
+        return Ok(());
+    }
+
+    if !options.retry_pending {
+        for pending in &queued {
+            print!(
+                "• entry {} ({:?}, queued {})",
+                pending.entry_id,
+                pending.session.mode,
+                pending.queued_at.format("%Y-%m-%d %H:%M")
+            );
+            if let Some(error) = &pending.last_error {
+                print!(
+                    " | {} failed attempts, last error: {error}",
+                    pending.attempts
+                );
+            }
+            println!();
+        }
+        println!("\nRun `journal analyze --pending` to retry them.");
+        return Ok(());
+    }
+
+    if local_only {
+        anyhow::bail!("Pending analyses need the AI backend and can't be retried with --private");
+    }
+
+    let config = JournalConfig::load(vault_path)?;
+    let runner = EffectRunner::with_config(vault_path.to_path_buf(), &config)?;
+
+    println!("🔍 Retrying {} pending analyses...", queued.len());
+    let report = runner.complete_pending_analyses().await?;
+    println!("✨ Added {} analyses to their entries.", report.completed);
+    if report.remaining > 0 {
+        println!(
+            "⚠️  {} analyses are still pending. Run `journal analyze` to see why.",
+            report.remaining
+        );
+    }
+
+    Ok(())
+}
+
+/// `journal reanalyze`: regenerate the analysis of existing entries with the current
+/// template and backend, keeping earlier analyses as previous versions
+pub async fn run_reanalyze(
+    vault_path: &Path,
+    options: &ReanalyzeOptions,
+    local_only: bool,
+) -> Result<()> {
+    if local_only {
+        anyhow::bail!("Reanalyzing entries needs the AI backend and can't be done with --private");
+    }
+
+    let entries = match options.entry_id {
+        Some(entry_id) => {
+            let doc = read_doc(vault_path, &entry_id)
+                .with_context(|| format!("Failed to read entry {entry_id}"))?;
+            if doc.doc_type != entries::ENTRY_DOC_TYPE {
+                anyhow::bail!(
+                    "{entry_id} is a {} document, not a journal entry",
+                    doc.doc_type
+                );
+            }
+            vec![doc]
+        }
+        None if options.filter == EntryFilter::default() => {
+            anyhow::bail!("Specify an entry ID, --since or --query to choose entries to reanalyze")
+        }
+        None => entries::find_entries(vault_path, &options.filter)?,
+    };
+
+    if entries.is_empty() {
+        println!("No journal entries match.");
+        return Ok(());
+    }
+
+    let confirmed = options.assume_yes
+        || entries.len() == 1
+        || dialoguer::Confirm::new()
+            .with_prompt(format!(
+                "Reanalyze {} entries? Each one is sent to the AI backend.",
+                entries.len()
+            ))
+            .default(false)
+            .interact()?;
+    if !confirmed {
+        println!("No entries were reanalyzed.");
+        return Ok(());
+    }
+
+    let config = JournalConfig::load(vault_path)?;
+    let runner = EffectRunner::with_config(vault_path.to_path_buf(), &config)?;
+
+    let mut failed = 0;
+    for entry in &entries {
+        match runner.reanalyze_entry(entry).await {
+            Ok(version) => println!(
+                "✨ {}: analysis version {} (template {})",
+                entry.uuid, version.version, version.template_hash
+            ),
+            Err(e) => {
+                failed += 1;
+                println!("⚠️  {}: {e:#}", entry.uuid);
+            }
+        }
+    }
+
+    println!(
+        "\nReanalyzed {} of {} entries.",
+        entries.len() - failed,
+        entries.len()
+    );
+    Ok(())
+}
+
+/// `journal digest`: write a weekly, monthly or yearly review of the entries in a period
+pub async fn run_digest(
+    vault_path: &Path,
+    options: &DigestOptions,
+    local_only: bool,
+) -> Result<()> {
+    let period = Period::containing(options.period, options.date);
+    let entries = digest::entries_in_period(vault_path, &period)?;
+    if entries.is_empty() {
+        println!(
+            "No journal entries between {} and {}.",
+            period.start, period.end
+        );
+        return Ok(());
+    }
+
+    let config = JournalConfig::load(vault_path)?;
+    let mut runner = EffectRunner::with_config(vault_path.to_path_buf(), &config)?;
+    runner.local_only = local_only;
+    // Digests need the current pack's digest doc type. An older pack is only replaced by
+    // `journal upgrade`, which migrates the existing docs first.
+    match pack::installed_version(vault_path) {
+        None => {
+            runner
+                .run_effect(Effect::InitializeVault(vault_path.to_path_buf()))
+                .await?;
+        }
+        Some(installed) if installed < pack::current_version() => anyhow::bail!(
+            "The vault has journal@{installed}, but digests need journal@{}. Run `journal upgrade` first.",
+            pack::current_version()
+        ),
+        Some(_) => {}
+    }
+
+    let stats = DigestStats::from_entries(&entries);
+    let material = digest::entries_for_synthesis(&entries);
+
+    let narrative = if local_only || material.is_empty() {
+        None
+    } else {
+        println!("🔍 Writing your {} review...", period_name(options.period));
+        let summary = stats.to_markdown();
+        match runner
+            .synthesize_digest(period_name(options.period), &summary, &material)
+            .await
+        {
+            Ok(narrative) => Some(narrative),
+            Err(e) => {
+                println!("⚠️  The narrative synthesis is unavailable: {e:#}");
+                None
+            }
+        }
+    };
+
+    let digest_id =
+        digest::write_digest(vault_path, &period, &entries, &stats, narrative.as_deref())?;
+
+    println!("\n📊 **{}**", period.title());
+    println!("{}", stats.to_markdown());
+    if let Some(narrative) = &narrative {
+        println!("\n{narrative}");
+    }
+    println!("\n📝 Digest saved to: docs/{digest_id}.md");
+    Ok(())
+}
+
+fn period_name(period: PeriodKind) -> &'static str {
+    match period {
+        PeriodKind::Week => "week",
+        PeriodKind::Month => "month",
+        PeriodKind::Year => "year",
+    }
+}
+
+/// `journal stats`: charts of mood, energy and journaling habits over time
+pub fn run_stats(vault_path: &Path, options: &StatsOptions) -> Result<()> {
+    let filter = EntryFilter {
+        since: options.since,
+        until: options.until,
+        ..EntryFilter::default()
+    };
+    let entries = entries::find_entries(vault_path, &filter)?;
+    let config = JournalConfig::load(vault_path)?;
+    let stats = TrendStats::from_entries(
+        vault_path,
+        &entries,
+        options.since,
+        options.until,
+        chrono::Local::now().date_naive(),
+        config.streaks.grace_days,
+    );
+
+    if options.json {
+        println!("{}", serde_json::to_string_pretty(&stats)?);
+    } else {
+        println!("{}", stats.render());
+    }
+    Ok(())
+}
+
+/// `journal sessions`: list paused and unfinished sessions, or with `--archived` the sessions
+/// that were written up as entries
+pub fn run_sessions(vault_path: &Path, options: &SessionsOptions) -> Result<()> {
+    let listed: Vec<_> = sessions::list_sessions(vault_path)?
+        .into_iter()
+        .filter(|session| match session.metadata.status {
+            SessionStatus::Archived => options.archived,
+            SessionStatus::Active | SessionStatus::Paused => {
+                !options.archived && !session.metadata.has_ended()
+            }
+            SessionStatus::Completed | SessionStatus::Abandoned => false,
+        })
+        .collect();
+
+    if listed.is_empty() {
+        match options.archived {
+            true => println!("No archived sessions."),
+            false => println!("No paused or unfinished sessions."),
+        }
+        return Ok(());
+    }
+
+    for session in &listed {
+        let id = session.metadata.session_doc_id.unwrap_or_default();
+        print!("• {id}: {}", sessions::describe(session));
+        if let Some(entry_id) = session.metadata.final_entry_id {
+            print!(" → entry {entry_id}");
+        }
+        println!();
+    }
+    if !options.archived {
+        println!("\nRun `journal resume <SESSION_UUID>` to continue one.");
+    }
+    Ok(())
+}
+
+/// `journal doctor`: check the vault for inconsistencies, and repair them with `--fix`
+pub async fn run_doctor(vault_path: &Path, options: &DoctorOptions) -> Result<()> {
+    let problems = doctor::diagnose(vault_path)?;
+    if problems.is_empty() {
+        println!("✅ No problems found in {}.", vault_path.display());
+        return Ok(());
+    }
+
+    let runner = match options.fix {
+        true => Some(EffectRunner::with_config(
+            vault_path.to_path_buf(),
+            &JournalConfig::load(vault_path)?,
+        )?),
+        false => None,
+    };
+    let mut unfixed = 0;
+    for problem in &problems {
+        println!("• {}", problem.describe());
+        let Some(runner) = &runner else {
+            println!("  fix: {}", problem.fix_description());
+            continue;
+        };
+        match doctor::fix(runner, problem).await {
+            Ok(()) => println!("  🔧 fixed: {}", problem.fix_description()),
+            Err(e) => {
+                unfixed += 1;
+                println!("  ⚠️  not fixed: {e:#}");
+            }
+        }
+    }
+
+    let count = match problems.len() {
+        1 => "1 problem".to_string(),
+        n => format!("{n} problems"),
+    };
+    match (options.fix, unfixed) {
+        (false, _) => println!("\nRun `journal doctor --fix` to repair them."),
+        (true, 0) => println!("\n✨ Repaired {count}."),
+        (true, unfixed) => println!("\n⚠️  Repaired {} of {count}.", problems.len() - unfixed),
+    }
+    Ok(())
+}
+
+/// `journal upgrade`: install the latest journal pack and migrate existing docs to its schemas,
+/// backing up every doc that's rewritten
+pub fn run_upgrade(vault_path: &Path, options: &UpgradeOptions) -> Result<()> {
+    let plan = upgrade::plan(vault_path)?;
+    if plan.is_empty() {
+        println!(
+            "✅ The vault already has the latest journal pack ({}).",
+            plan.to
+        );
+        return Ok(());
+    }
+
+    let from = plan
+        .from
+        .map_or_else(|| "none".to_string(), |from| from.to_string());
+    println!("Journal pack: {from} → {}", plan.to);
+    for migration in upgrade::MIGRATIONS {
+        let count = plan
+            .changes
+            .iter()
+            .filter(|change| change.migrations.contains(&migration.description))
+            .count();
+        if count > 0 {
+            let docs = if count == 1 { "doc" } else { "docs" };
+            println!("• {} ({count} {docs})", migration.description);
+        }
+    }
+
+    if options.dry_run {
+        println!("\nDry run: nothing was changed. Run `journal upgrade` to apply.");
+        return Ok(());
+    }
+
+    if let Some(backup_dir) = upgrade::apply(vault_path, &plan)? {
+        println!(
+            "\n💾 Backed up the original docs to {}",
+            backup_dir.display()
+        );
+    }
+    println!("✨ Upgraded the vault to journal@{}.", plan.to);
+    Ok(())
+}
+
+/// `journal tags`: list the tags used across entries, or change them
+pub fn run_tags(vault_path: &Path, command: &TagsCommand) -> Result<()> {
+    match command {
+        TagsCommand::List => {
+            let vocabulary = tags::vocabulary(vault_path)?;
+            if vocabulary.is_empty() {
+                println!("No entries are tagged yet. Tag a session with /tag, or run `journal tags add`.");
+                return Ok(());
+            }
+            let width = vocabulary
+                .iter()
+                .map(|count| count.tag.len())
+                .max()
+                .unwrap_or(0);
+            for count in vocabulary {
+                println!("{:<width$}  {}", count.tag, entry_count(count.entries));
+            }
+        }
+        TagsCommand::Add { entry_id, tags } => {
+            let entry_tags = tags::add(vault_path, *entry_id, tags)?;
+            println!("🏷️  {entry_id} is tagged: {}", entry_tags.join(", "));
+        }
+        TagsCommand::Remove { entry_id, tags } => {
+            let entry_tags = tags::remove(vault_path, *entry_id, tags)?;
+            match entry_tags.is_empty() {
+                true => println!("🏷️  {entry_id} has no tags left."),
+                false => println!("🏷️  {entry_id} is tagged: {}", entry_tags.join(", ")),
+            }
+        }
+        TagsCommand::Rename { from, to } => {
+            let changed = tags::rename(vault_path, from, to)?;
+            println!(
+                "🏷️  Renamed '{from}' to '{to}' on {}.",
+                entry_count(changed)
+            );
+        }
+        TagsCommand::Merge { tags, into } => {
+            let changed = tags::merge(vault_path, tags, into)?;
+            println!(
+                "🏷️  Merged {} into '{into}' on {}.",
+                tags.join(", "),
+                entry_count(changed)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `journal people`: everyone and everything mentioned across entries, with how often and the
+/// mood of those entries
+pub fn run_people(vault_path: &Path, options: &PeopleOptions) -> Result<()> {
+    let config = JournalConfig::load(vault_path)?;
+    if options.reindex {
+        let changed = mentions::reindex(vault_path, &config.mentions)?;
+        println!("🔎 Updated the mentions of {}.\n", entry_count(changed));
+    }
+
+    let people = mentions::people(vault_path, &config.mentions)?;
+    if people.is_empty() {
+        println!(
+            "No mentions found yet. Run `journal people --reindex` to look through older entries."
+        );
+        return Ok(());
+    }
+
+    let width = people
+        .iter()
+        .map(|person| person.name.chars().count())
+        .max()
+        .unwrap_or(0);
+    for person in people {
+        let entries = entry_count(person.entries);
+        let mood = person
+            .average_mood
+            .map_or_else(|| "-".to_string(), |mood| format!("{mood:.1}/3"));
+        println!(
+            "{:<width$}  {entries:<11}  last {}  mood {mood}",
+            person.name, person.last_mentioned
+        );
+    }
+    println!("\nRun `journal mentions <NAME>` to see what you wrote.");
+    Ok(())
+}
+
+/// `journal mentions`: every entry that mentions a name, with the lines that mention it
+pub fn run_mentions(vault_path: &Path, options: &MentionsOptions) -> Result<()> {
+    let config = JournalConfig::load(vault_path)?;
+    let name = mentions::canonical(&options.name, &config.mentions);
+    let found = mentions::find(vault_path, &name, &config.mentions)?;
+    if found.is_empty() {
+        println!("No entries mention {name}.");
+        return Ok(());
+    }
+
+    println!("{name}: {}\n", entry_count(found.len()));
+    for mention in found {
+        let mood = mention.mood.as_deref().unwrap_or("unknown");
+        println!("{}  {}  (mood: {mood})", mention.date, mention.title);
+        for snippet in &mention.snippets {
+            println!("  “{snippet}”");
+        }
+        println!("  {}\n", mention.entry_id);
+    }
+    Ok(())
+}
+
+/// `journal star`: star a line of an entry's transcript, or list the lines that can be starred
+pub fn run_star(vault_path: &Path, options: &StarOptions) -> Result<()> {
+    let entry_id = options.entry_id;
+    if let Some(line) = options.line {
+        match highlights::star(vault_path, entry_id, line)? {
+            Some(highlight) => println!(
+                "⭐ Starred {}: “{}”",
+                speaker_name(highlight.speaker),
+                highlight.quote
+            ),
+            None => println!("Line {line} is already starred."),
+        }
+        return Ok(());
+    }
+
+    let entry = entries::read_entry(vault_path, entry_id)?;
+    let starred: Vec<Uuid> = highlights::entry_highlights(&entry)
+        .iter()
+        .map(|highlight| highlight.line_id)
+        .collect();
+    let lines = highlights::transcript_lines(vault_path, &entry)?;
+    let width = lines.len().to_string().len();
+    for (number, line) in lines.iter().enumerate() {
+        let star = match line.starred || starred.contains(&line.id) {
+            true => "⭐",
+            false => "  ",
+        };
+        println!(
+            "{:>width$} {star} {}: {}",
+            number + 1,
+            speaker_name(line.speaker),
+            line.content
+        );
+    }
+    println!("\nRun `journal star {entry_id} <LINE>` to star a line.");
+    Ok(())
+}
+
+/// `journal highlights`: starred lines across entries, grouped by entry
+pub fn run_highlights(vault_path: &Path, options: &HighlightsOptions) -> Result<()> {
+    let quotes = highlights::collect(vault_path, &options.filter)?;
+    if quotes.is_empty() {
+        match options.filter == EntryFilter::default() {
+            true => println!(
+                "No highlights yet. Star a line with /star during a session, or run `journal star`."
+            ),
+            false => println!("No highlights match."),
+        }
+        return Ok(());
+    }
+
+    let mut current_entry = None;
+    for quote in quotes {
+        if current_entry != Some(quote.entry_id) {
+            if current_entry.is_some() {
+                println!();
+            }
+            current_entry = Some(quote.entry_id);
+            let mode = match quote.mode {
+                Some(SessionMode::Morning) => "morning",
+                Some(SessionMode::Evening) => "evening",
+                None => "entry",
+            };
+            let tags: String = quote.tags.iter().map(|tag| format!("  #{tag}")).collect();
+            println!("{} {mode}{tags}", quote.date);
+        }
+        let quote_speaker = match quote.highlight.speaker {
+            Speaker::User => String::new(),
+            speaker => format!(" ({})", speaker_name(speaker)),
+        };
+        println!("  ⭐ “{}”{quote_speaker}", quote.highlight.quote);
+    }
+    Ok(())
+}
+
+fn speaker_name(speaker: Speaker) -> &'static str {
+    match speaker {
+        Speaker::User => "You",
+        Speaker::Coach => "Coach",
+        Speaker::System => "System",
+    }
+}
+
+fn entry_count(count: usize) -> String {
+    match count {
+        1 => "1 entry".to_string(),
+        n => format!("{n} entries"),
+    }
+}
+
+/// `journal streak`: streaks per mode and progress towards weekly goals
+pub fn run_streak(vault_path: &Path) -> Result<()> {
+    let config = JournalConfig::load(vault_path)?;
+    let status = StreakStatus::load(
+        vault_path,
+        &config.streaks,
+        chrono::Local::now().date_naive(),
+    )?;
+    println!("{}", status.details(config.streaks.grace_days));
+    Ok(())
+}
@@ -0,0 +1,172 @@
+use crate::state::SessionMode;
+use crate::streak::Goal;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Location of the journal configuration file, relative to the vault root
+pub const CONFIG_FILE: &str = ".aethel/journal.config.json";
+
+/// User configuration for the journal, stored in the vault
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JournalConfig {
+    pub redaction: RedactionConfig,
+    pub resurfacing: ResurfacingConfig,
+    pub streaks: StreakConfig,
+    pub mentions: MentionsConfig,
+    /// Session modes that always run in local-only mode, without any AI backend
+    pub private_modes: Vec<SessionMode>,
+}
+
+/// Controls what is scrubbed from text before it is sent to the AI backend
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionConfig {
+    pub enabled: bool,
+    /// Names to replace with consistent placeholders like `[Person A]`
+    pub names: Vec<String>,
+    /// Additional regular expressions whose matches are redacted
+    pub patterns: Vec<String>,
+    pub detect_emails: bool,
+    pub detect_phones: bool,
+    pub detect_addresses: bool,
+}
+
+impl Default for RedactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            names: Vec::new(),
+            patterns: Vec::new(),
+            detect_emails: true,
+            detect_phones: true,
+            detect_addresses: true,
+        }
+    }
+}
+
+/// "On this day" snippets of past entries shown at the start of a session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResurfacingConfig {
+    pub enabled: bool,
+    /// Let the AI coach see the snippets so it can refer back to them
+    pub share_with_coach: bool,
+}
+
+impl Default for ResurfacingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            share_with_coach: false,
+        }
+    }
+}
+
+/// How streaks are counted and which weekly goals to track
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreakConfig {
+    /// Missed days in a row that don't break a streak
+    pub grace_days: u32,
+    /// Weekly goals such as "evening 5x/week"
+    pub goals: Vec<Goal>,
+}
+
+impl Default for StreakConfig {
+    fn default() -> Self {
+        Self {
+            grace_days: 1,
+            goals: Vec::new(),
+        }
+    }
+}
+
+/// How people, places and projects are picked out of entries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MentionsConfig {
+    /// Names and the other ways they're written, e.g. "Sarah": ["Sare", "my sister"]
+    pub aliases: BTreeMap<String, Vec<String>>,
+    /// Treat capitalized phrases like "Project Atlas" as mentions
+    pub detect_capitalized: bool,
+    /// Capitalized words that are never mentions
+    pub ignore: Vec<String>,
+    /// Also ask the AI backend for the mentions in each analyzed session
+    pub ai_extraction: bool,
+}
+
+impl Default for MentionsConfig {
+    fn default() -> Self {
+        Self {
+            aliases: BTreeMap::new(),
+            detect_capitalized: true,
+            ignore: Vec::new(),
+            ai_extraction: false,
+        }
+    }
+}
+
+impl JournalConfig {
+    pub fn path(vault_path: &Path) -> PathBuf {
+        vault_path.join(CONFIG_FILE)
+    }
+
+    /// Load the config from the vault, falling back to defaults if no file exists
+    pub fn load(vault_path: &Path) -> Result<Self> {
+        let path = Self::path(vault_path);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(&path).context("Failed to read journal config")?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse journal config at {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_load_missing_config_uses_defaults() {
+        let temp_dir = TempDir::new().unwrap();
+        let config = JournalConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config, JournalConfig::default());
+        assert!(config.redaction.enabled);
+    }
+
+    #[test]
+    fn test_load_partial_config() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join(".aethel")).unwrap();
+        std::fs::write(
+            JournalConfig::path(temp_dir.path()),
+            r#"{ "redaction": { "names": ["Sarah"], "detect_phones": false }, "private_modes": ["Evening"], "streaks": { "goals": ["evening 5x/week"] } }"#,
+        )
+        .unwrap();
+
+        let config = JournalConfig::load(temp_dir.path()).unwrap();
+        assert_eq!(config.redaction.names, vec!["Sarah".to_string()]);
+        assert!(!config.redaction.detect_phones);
+        assert!(config.redaction.detect_emails);
+        assert_eq!(config.private_modes, vec![SessionMode::Evening]);
+        assert!(config.resurfacing.enabled);
+        assert!(!config.resurfacing.share_with_coach);
+        assert_eq!(config.streaks.grace_days, 1);
+        assert_eq!(config.streaks.goals[0].times_per_week, 5);
+    }
+
+    #[test]
+    fn test_load_invalid_config_fails() {
+        let temp_dir = TempDir::new().unwrap();
+        std::fs::create_dir_all(temp_dir.path().join(".aethel")).unwrap();
+        std::fs::write(JournalConfig::path(temp_dir.path()), "not json").unwrap();
+
+        assert!(JournalConfig::load(temp_dir.path()).is_err());
+    }
+}
@@ -0,0 +1,312 @@
+use crate::entries::{self, EntryFilter};
+use crate::pack;
+use aethel_core::{Doc, Patch, PatchMode};
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+use uuid::Uuid;
+
+pub const DIGEST_DOC_TYPE: &str = "journal.digest";
+
+/// Number of most frequent words reported as themes
+const THEME_COUNT: usize = 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PeriodKind {
+    Week,
+    Month,
+    Year,
+}
+
+/// The calendar week (Monday to Sunday), month or year being reviewed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Period {
+    pub kind: PeriodKind,
+    pub start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl Period {
+    /// The period of the given kind that contains `date`
+    pub fn containing(kind: PeriodKind, date: NaiveDate) -> Self {
+        let (start, next_start) = match kind {
+            PeriodKind::Week => {
+                let start = date - Duration::days(date.weekday().num_days_from_monday() as i64);
+                (start, start + Duration::days(7))
+            }
+            PeriodKind::Month => {
+                let start = date.with_day(1).expect("every month has a first day");
+                let next_start = if date.month() == 12 {
+                    NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)
+                } else {
+                    NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)
+                };
+                (start, next_start.expect("first of the month is valid"))
+            }
+            PeriodKind::Year => (
+                NaiveDate::from_ymd_opt(date.year(), 1, 1).expect("January 1st is valid"),
+                NaiveDate::from_ymd_opt(date.year() + 1, 1, 1).expect("January 1st is valid"),
+            ),
+        };
+
+        Self {
+            kind,
+            start,
+            end: next_start - Duration::days(1),
+        }
+    }
+
+    pub fn title(&self) -> String {
+        match self.kind {
+            PeriodKind::Week => {
+                format!("Weekly Review - week of {}", self.start.format("%Y-%m-%d"))
+            }
+            PeriodKind::Month => format!("Monthly Review - {}", self.start.format("%B %Y")),
+            PeriodKind::Year => format!("Yearly Review - {}", self.start.format("%Y")),
+        }
+    }
+}
+
+/// Statistics computed locally from the entries in a period
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DigestStats {
+    pub sessions: usize,
+    /// Average mood from 1 (challenging) to 3 (positive)
+    pub average_mood: Option<f64>,
+    /// Average energy from 1 (low) to 3 (high)
+    pub average_energy: Option<f64>,
+    pub themes: Vec<String>,
+    pub action_items_completed: usize,
+    pub action_items_open: usize,
+}
+
+impl DigestStats {
+    pub fn from_entries(entries: &[Doc]) -> Self {
+        let bodies = entries.iter().map(|entry| entry.body.as_str());
+        Self {
+            sessions: entries.len(),
+            average_mood: average(entries.iter().filter_map(entries::mood_score)),
+            average_energy: average(entries.iter().filter_map(entries::energy_score)),
+            themes: entries::top_themes(entries, THEME_COUNT),
+            action_items_completed: bodies
+                .clone()
+                .map(|body| body.matches("- [x]").count() + body.matches("- [X]").count())
+                .sum(),
+            action_items_open: bodies.map(|body| body.matches("- [ ]").count()).sum(),
+        }
+    }
+
+    pub fn to_markdown(&self) -> String {
+        let score = |value: Option<f64>| {
+            value
+                .map(|v| format!("{v:.1} / 3"))
+                .unwrap_or_else(|| "-".to_string())
+        };
+        let themes = if self.themes.is_empty() {
+            "-".to_string()
+        } else {
+            self.themes.join(", ")
+        };
+
+        format!(
+            "- Sessions: {}\n\
+            - Average mood: {}\n\
+            - Average energy: {}\n\
+            - Most common themes: {}\n\
+            - Action items completed: {} (open: {})",
+            self.sessions,
+            score(self.average_mood),
+            score(self.average_energy),
+            themes,
+            self.action_items_completed,
+            self.action_items_open
+        )
+    }
+}
+
+pub fn average(values: impl Iterator<Item = f64>) -> Option<f64> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), value| (sum + value, count + 1));
+    (count > 0).then(|| sum / count as f64)
+}
+
+/// Journal entries created during `period`, oldest first
+pub fn entries_in_period(vault_path: &Path, period: &Period) -> Result<Vec<Doc>> {
+    let filter = EntryFilter {
+        since: Some(period.start),
+        until: Some(period.end),
+        ..EntryFilter::default()
+    };
+    entries::find_entries(vault_path, &filter)
+}
+
+/// Material from each shareable entry for the narrative synthesis: its analysis when it has
+/// one, otherwise its transcript
+pub fn entries_for_synthesis(entries: &[Doc]) -> String {
+    entries
+        .iter()
+        .filter(|entry| entries::shareable_with_ai(entry))
+        .map(|entry| {
+            let (analysis, _) = entries::split_analysis_sections(&entry.body, "AI Analysis");
+            let content = if analysis.is_empty() || analysis.contains("AI Analysis Pending") {
+                entries::user_lines(&entry.body)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            } else {
+                analysis.to_string()
+            };
+            format!("### {}\n\n{}", entries::entry_title(entry), content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Write the digest doc, linking every entry it covers. Returns the new doc's UUID.
+pub fn write_digest(
+    vault_path: &Path,
+    period: &Period,
+    entries: &[Doc],
+    stats: &DigestStats,
+    narrative: Option<&str>,
+) -> Result<Uuid> {
+    let links = entries
+        .iter()
+        .map(|entry| format!("- [{}]({}.md)", entries::entry_title(entry), entry.uuid))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let synthesis = narrative
+        .unwrap_or("_No narrative synthesis: the AI backend was not used for this digest._");
+
+    let body = format!(
+        "# {}\n\n{} to {}\n\n## Statistics\n\n{}\n\n## Synthesis\n\n{}\n\n## Entries\n\n{}",
+        period.title(),
+        period.start.format("%Y-%m-%d"),
+        period.end.format("%Y-%m-%d"),
+        stats.to_markdown(),
+        synthesis,
+        links
+    );
+
+    let patch = Patch {
+        uuid: None,
+        doc_type: Some(DIGEST_DOC_TYPE.to_string()),
+        mode: PatchMode::Create,
+        frontmatter: Some(json!({
+            "period": period.kind,
+            "start": period.start,
+            "end": period.end,
+            "title": period.title(),
+            "entry_ids": entries.iter().map(|entry| entry.uuid).collect::<Vec<_>>(),
+            "stats": stats,
+            "ai_processed": narrative.is_some(),
+        })),
+        body: Some(body),
+    };
+
+    let write_result =
+        pack::apply_checked(vault_path, patch).context("Failed to create digest document")?;
+    Ok(write_result.uuid)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aethel_core::read_doc;
+    use serde_json::Map;
+
+    fn entry(mood: &str, energy: &str, body: &str) -> Doc {
+        let mut frontmatter = Map::new();
+        frontmatter.insert("mood".to_string(), json!(mood));
+        frontmatter.insert("energy".to_string(), json!(energy));
+        frontmatter.insert("title".to_string(), json!("Evening Journal Entry"));
+        Doc {
+            uuid: Uuid::new_v4(),
+            doc_type: entries::ENTRY_DOC_TYPE.to_string(),
+            created: chrono::Utc::now(),
+            updated: chrono::Utc::now(),
+            v: "1.0.0".to_string(),
+            tags: Vec::new(),
+            frontmatter_extra: frontmatter,
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_period_containing() {
+        // 2024-03-14 was a Thursday
+        let date = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+
+        let week = Period::containing(PeriodKind::Week, date);
+        assert_eq!(week.start, NaiveDate::from_ymd_opt(2024, 3, 11).unwrap());
+        assert_eq!(week.end, NaiveDate::from_ymd_opt(2024, 3, 17).unwrap());
+
+        let month = Period::containing(PeriodKind::Month, date);
+        assert_eq!(month.end, NaiveDate::from_ymd_opt(2024, 3, 31).unwrap());
+        let december = Period::containing(
+            PeriodKind::Month,
+            NaiveDate::from_ymd_opt(2024, 12, 5).unwrap(),
+        );
+        assert_eq!(december.end, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+
+        let year = Period::containing(PeriodKind::Year, date);
+        assert_eq!(year.start, NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        assert_eq!(year.end, NaiveDate::from_ymd_opt(2024, 12, 31).unwrap());
+    }
+
+    #[test]
+    fn test_digest_stats() {
+        let entries = vec![
+            entry(
+                "positive",
+                "high",
+                "You: Running with friends\n\n- [x] Book the trip\n- [ ] Call mum",
+            ),
+            entry("challenging", "low", "You: Running late, friends cancelled"),
+        ];
+
+        let stats = DigestStats::from_entries(&entries);
+        assert_eq!(stats.sessions, 2);
+        assert_eq!(stats.average_mood, Some(2.0));
+        assert_eq!(stats.average_energy, Some(2.0));
+        assert_eq!(stats.themes, vec!["friends", "running"]);
+        assert_eq!(stats.action_items_completed, 1);
+        assert_eq!(stats.action_items_open, 1);
+    }
+
+    #[test]
+    fn test_private_entries_are_not_synthesized() {
+        let shared = entry("neutral", "medium", "You: Shared thoughts");
+        let mut private = entry("neutral", "medium", "You: Private thoughts");
+        private
+            .frontmatter_extra
+            .insert("analysis_status".to_string(), json!("local"));
+
+        let material = entries_for_synthesis(&[shared, private]);
+        assert!(material.contains("Shared thoughts"));
+        assert!(!material.contains("Private thoughts"));
+    }
+
+    #[test]
+    fn test_write_digest_links_entries() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let period = Period::containing(
+            PeriodKind::Week,
+            NaiveDate::from_ymd_opt(2024, 3, 14).unwrap(),
+        );
+        let entries = vec![entry("positive", "high", "You: A good week")];
+        let stats = DigestStats::from_entries(&entries);
+
+        let digest_id = write_digest(temp_dir.path(), &period, &entries, &stats, None).unwrap();
+        let doc = read_doc(temp_dir.path(), &digest_id).unwrap();
+
+        assert_eq!(doc.doc_type, DIGEST_DOC_TYPE);
+        assert_eq!(doc.frontmatter_extra.get("period"), Some(&json!("week")));
+        assert_eq!(
+            doc.frontmatter_extra.get("ai_processed"),
+            Some(&json!(false))
+        );
+        assert!(doc.body.contains(&format!("({}.md)", entries[0].uuid)));
+    }
+}
@@ -0,0 +1,476 @@
+use crate::effects::EffectRunner;
+use crate::entries::ENTRY_DOC_TYPE;
+use crate::index::{IndexStore, JournalIndex, INDEX_FILE};
+use crate::pack::{self, PackVersion};
+use crate::sessions::{self, SESSION_DOC_TYPE};
+use crate::state::{AnalysisStatus, SessionMode, Speaker, TranscriptEntry};
+use crate::{stats, update, upgrade};
+use aethel_core::{read_doc, Doc, Patch, PatchMode};
+use anyhow::{Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Something wrong with the vault that `journal doctor` can find
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// A doc file that can't be read or parsed, with its path relative to the vault root
+    UnreadableDoc { path: PathBuf, error: String },
+    /// The index file can't be parsed
+    CorruptIndex,
+    /// The index names a session doc that doesn't exist
+    IndexMissingSession(Uuid),
+    /// A session was stopped but no entry was written for it
+    SessionWithoutEntry(Uuid),
+    /// An entry links to a session doc that doesn't exist
+    EntryMissingSession { entry_id: Uuid, session_id: Uuid },
+    /// A doc's frontmatter doesn't match its type's schema
+    SchemaViolation {
+        doc_id: Uuid,
+        doc_type: String,
+        errors: Vec<String>,
+    },
+    /// A session doc whose body isn't a transcript
+    UnparseableTranscript { session_id: Uuid, error: String },
+    /// Installed pack files that differ from the ones this version ships
+    OutdatedPack(Vec<String>),
+    /// The vault's newest pack is older than the one this version ships
+    PackUpgradeAvailable {
+        installed: PackVersion,
+        current: PackVersion,
+    },
+}
+
+impl Problem {
+    pub fn describe(&self) -> String {
+        match self {
+            Problem::UnreadableDoc { path, error } => {
+                format!("{} can't be read: {error}", path.display())
+            }
+            Problem::CorruptIndex => "The journal index can't be read".to_string(),
+            Problem::IndexMissingSession(session_id) => {
+                format!("The journal index points at session {session_id}, which doesn't exist")
+            }
+            Problem::SessionWithoutEntry(session_id) => {
+                format!("Session {session_id} was completed but has no journal entry")
+            }
+            Problem::EntryMissingSession {
+                entry_id,
+                session_id,
+            } => format!("Entry {entry_id} links to session {session_id}, which doesn't exist"),
+            Problem::SchemaViolation {
+                doc_id,
+                doc_type,
+                errors,
+            } => format!(
+                "{doc_id} doesn't match the {doc_type} schema: {}",
+                errors.join("; ")
+            ),
+            Problem::UnparseableTranscript { session_id, error } => {
+                format!("Session {session_id} has a transcript that can't be read: {error}")
+            }
+            Problem::OutdatedPack(files) => {
+                format!(
+                    "The installed journal pack is out of date ({})",
+                    files.join(", ")
+                )
+            }
+            Problem::PackUpgradeAvailable { installed, current } => {
+                format!("The vault has journal pack {installed}, but {current} is available")
+            }
+        }
+    }
+
+    /// What `--fix` does about the problem
+    pub fn fix_description(&self) -> &'static str {
+        match self {
+            Problem::UnreadableDoc { .. } => "nothing; repair or remove the file by hand",
+            Problem::CorruptIndex | Problem::IndexMissingSession(_) => {
+                "rebuild the index from your session docs"
+            }
+            Problem::SessionWithoutEntry(_) => {
+                "write the entry now; its AI analysis is queued for `journal analyze --pending`"
+            }
+            Problem::EntryMissingSession { .. } => "remove the entry's link to the missing session",
+            Problem::SchemaViolation { .. } => "fill in missing fields that can be worked out",
+            Problem::UnparseableTranscript { .. } => {
+                "keep the unreadable text as a single transcript line"
+            }
+            Problem::OutdatedPack(_) => "reinstall the journal pack",
+            Problem::PackUpgradeAvailable { .. } => {
+                "migrate your docs and install the new pack, as `journal upgrade` does"
+            }
+        }
+    }
+}
+
+/// Scan the vault for inconsistencies
+pub fn diagnose(vault_path: &Path) -> Result<Vec<Problem>> {
+    let (docs, mut problems) = scan_docs(vault_path)?;
+
+    let index_path = vault_path.join(INDEX_FILE);
+    if index_path.exists() {
+        let index = fs::read_to_string(&index_path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<JournalIndex>(&content).ok());
+        match index {
+            None => problems.push(Problem::CorruptIndex),
+            Some(JournalIndex {
+                active_session: Some(session_id),
+                ..
+            }) if !is_session(&docs, session_id) => {
+                problems.push(Problem::IndexMissingSession(session_id))
+            }
+            Some(_) => {}
+        }
+    }
+
+    for doc in &docs {
+        let errors = pack::validate(&doc.doc_type, &Value::Object(doc.frontmatter_extra.clone()))?;
+        if !errors.is_empty() {
+            problems.push(Problem::SchemaViolation {
+                doc_id: doc.uuid,
+                doc_type: doc.doc_type.clone(),
+                errors,
+            });
+        }
+
+        match doc.doc_type.as_str() {
+            SESSION_DOC_TYPE => {
+                if let Err(e) = serde_json::from_str::<Vec<TranscriptEntry>>(&doc.body) {
+                    problems.push(Problem::UnparseableTranscript {
+                        session_id: doc.uuid,
+                        error: e.to_string(),
+                    });
+                } else if let Ok(session) = sessions::load_session(vault_path, doc.uuid) {
+                    // Sessions written before entries were linked from them are found through
+                    // the entry's `session_id`
+                    let has_entry = docs.iter().any(|other| {
+                        Some(other.uuid) == session.metadata.final_entry_id
+                            || (other.doc_type == ENTRY_DOC_TYPE
+                                && entry_session_id(other) == Some(doc.uuid))
+                    });
+                    if session.metadata.completed_at.is_some() && !has_entry {
+                        problems.push(Problem::SessionWithoutEntry(doc.uuid));
+                    }
+                }
+            }
+            ENTRY_DOC_TYPE => {
+                if let Some(session_id) = entry_session_id(doc).filter(|id| !is_session(&docs, *id))
+                {
+                    problems.push(Problem::EntryMissingSession {
+                        entry_id: doc.uuid,
+                        session_id,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let current = pack::current_version();
+    match pack::installed_version(vault_path) {
+        Some(installed) if installed < current => {
+            problems.push(Problem::PackUpgradeAvailable { installed, current });
+        }
+        _ => {
+            let outdated = pack::outdated_files(vault_path);
+            if !outdated.is_empty() {
+                problems.push(Problem::OutdatedPack(outdated));
+            }
+        }
+    }
+    Ok(problems)
+}
+
+/// Repair one problem found by `diagnose`
+pub async fn fix(runner: &EffectRunner, problem: &Problem) -> Result<()> {
+    let vault_path = runner.vault_path.as_path();
+    match problem {
+        Problem::UnreadableDoc { path, .. } => {
+            anyhow::bail!("needs manual repair: {}", path.display())
+        }
+        Problem::CorruptIndex | Problem::IndexMissingSession(_) => {
+            IndexStore::new(vault_path).rebuild()?;
+        }
+        Problem::SessionWithoutEntry(session_id) => {
+            let mut session = sessions::load_session(vault_path, *session_id)?;
+            let (analysis, analysis_status) = if session.metadata.private {
+                (stats::local_summary(&session), AnalysisStatus::Local)
+            } else {
+                (
+                    update::pending_analysis_notice("the entry was written by `journal doctor`"),
+                    AnalysisStatus::Pending,
+                )
+            };
+            let written = runner
+                .create_final_entry(&session, &analysis, analysis_status)
+                .await?;
+            session.archive(written.uuid);
+            sessions::update_metadata(vault_path, &session)?;
+        }
+        Problem::EntryMissingSession { entry_id, .. } => {
+            merge_frontmatter(vault_path, *entry_id, json!({ "session_id": null }))?;
+        }
+        Problem::SchemaViolation { doc_id, errors, .. } => {
+            let doc = read_doc(vault_path, doc_id).context("Failed to read doc")?;
+            let fields = derivable_fields(&doc);
+            if fields.is_empty() {
+                anyhow::bail!("needs manual repair: {}", errors.join("; "));
+            }
+            // Checked against the schema, so this fails if the fields aren't enough
+            merge_frontmatter(vault_path, *doc_id, Value::Object(fields))?;
+        }
+        Problem::UnparseableTranscript { session_id, .. } => {
+            let doc = read_doc(vault_path, session_id).context("Failed to read session doc")?;
+            let entry = TranscriptEntry {
+                id: Uuid::new_v4(),
+                timestamp: doc.created,
+                speaker: Speaker::System,
+                content: doc.body,
+                starred: false,
+                revisions: Vec::new(),
+            };
+            pack::apply_checked(
+                vault_path,
+                Patch {
+                    uuid: Some(*session_id),
+                    doc_type: None,
+                    mode: PatchMode::ReplaceBody,
+                    frontmatter: None,
+                    body: Some(serde_json::to_string_pretty(&[entry])?),
+                },
+            )
+            .context("Failed to rewrite the transcript")?;
+        }
+        Problem::OutdatedPack(_) => pack::install(vault_path)?,
+        Problem::PackUpgradeAvailable { .. } => {
+            upgrade::apply(vault_path, &upgrade::plan(vault_path)?)?;
+        }
+    }
+    Ok(())
+}
+
+/// Every readable doc in the vault. Files that aren't docs are skipped, and so are docs that
+/// can't be read, which `diagnose` reports.
+pub fn read_docs(vault_path: &Path) -> Result<Vec<Doc>> {
+    Ok(scan_docs(vault_path)?.0)
+}
+
+/// Every readable doc in the vault, and an `UnreadableDoc` problem for each doc that isn't
+fn scan_docs(vault_path: &Path) -> Result<(Vec<Doc>, Vec<Problem>)> {
+    let docs_dir = vault_path.join("docs");
+    if !docs_dir.exists() {
+        return Ok((Vec::new(), Vec::new()));
+    }
+
+    let mut docs = Vec::new();
+    let mut unreadable = Vec::new();
+    for dir_entry in fs::read_dir(&docs_dir).context("Failed to read docs directory")? {
+        let path = dir_entry?.path();
+        let Some(uuid) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| Uuid::parse_str(stem).ok())
+        else {
+            continue;
+        };
+        match read_doc(vault_path, &uuid) {
+            Ok(doc) => docs.push(doc),
+            Err(e) => unreadable.push(Problem::UnreadableDoc {
+                path: path.strip_prefix(vault_path).unwrap_or(&path).to_path_buf(),
+                error: format!("{e:#}"),
+            }),
+        }
+    }
+    docs.sort_by_key(|doc| doc.created);
+    Ok((docs, unreadable))
+}
+
+fn entry_session_id(entry: &Doc) -> Option<Uuid> {
+    entry
+        .frontmatter_extra
+        .get("session_id")
+        .and_then(|id| id.as_str())
+        .and_then(|id| Uuid::parse_str(id).ok())
+}
+
+fn is_session(docs: &[Doc], uuid: Uuid) -> bool {
+    docs.iter()
+        .any(|doc| doc.uuid == uuid && doc.doc_type == SESSION_DOC_TYPE)
+}
+
+/// Required fields missing from a doc that can be worked out from the rest of it
+fn derivable_fields(doc: &Doc) -> serde_json::Map<String, Value> {
+    let frontmatter = &doc.frontmatter_extra;
+    let mode = frontmatter
+        .get("mode")
+        .cloned()
+        .and_then(|mode| serde_json::from_value::<SessionMode>(mode).ok())
+        .or_else(|| match frontmatter.get("session_type")?.as_str()? {
+            "morning" => Some(SessionMode::Morning),
+            "evening" => Some(SessionMode::Evening),
+            _ => None,
+        });
+    let (mode_name, session_type) = match mode {
+        Some(SessionMode::Morning) => ("Morning", "morning"),
+        Some(SessionMode::Evening) => ("Evening", "evening"),
+        None => return serde_json::Map::new(),
+    };
+
+    let mut fields = serde_json::Map::new();
+    fields.insert("mode".to_string(), json!(mode_name));
+    fields.insert("session_type".to_string(), json!(session_type));
+    match doc.doc_type.as_str() {
+        ENTRY_DOC_TYPE => {
+            fields.insert("session_id".to_string(), Value::Null);
+            fields.insert(
+                "title".to_string(),
+                json!(format!(
+                    "{mode_name} Journal Entry - {}",
+                    doc.created.format("%Y-%m-%d")
+                )),
+            );
+        }
+        SESSION_DOC_TYPE => {
+            fields.insert("metadata".to_string(), json!({}));
+        }
+        _ => return serde_json::Map::new(),
+    }
+    // Mode and session type are rewritten to agree with each other
+    fields
+        .retain(|key, _| !frontmatter.contains_key(key) || key == "mode" || key == "session_type");
+    fields
+}
+
+fn merge_frontmatter(vault_path: &Path, uuid: Uuid, frontmatter: Value) -> Result<()> {
+    pack::apply_checked(
+        vault_path,
+        Patch {
+            uuid: Some(uuid),
+            doc_type: None,
+            mode: PatchMode::MergeFrontmatter,
+            frontmatter: Some(frontmatter),
+            body: None,
+        },
+    )
+    .with_context(|| format!("Failed to update {uuid}"))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::JournalSession;
+    use aethel_core::apply_patch;
+    use tempfile::TempDir;
+
+    fn create(vault_path: &Path, doc_type: &str, frontmatter: Value, body: &str) -> Uuid {
+        apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(doc_type.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(frontmatter),
+                body: Some(body.to_string()),
+            },
+        )
+        .unwrap()
+        .uuid
+    }
+
+    #[tokio::test]
+    async fn test_diagnose_and_fix() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+        let runner = EffectRunner::new(vault_path.to_path_buf());
+        pack::install(vault_path).unwrap();
+        assert!(diagnose(vault_path).unwrap().is_empty());
+
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(crate::state::Speaker::User, "A full day".to_string());
+        session.mark_completed();
+        let stopped = create(
+            vault_path,
+            SESSION_DOC_TYPE,
+            json!({ "mode": "Evening", "session_type": "evening", "metadata": session.metadata }),
+            &serde_json::to_string(&session.transcript).unwrap(),
+        );
+        let garbled = create(
+            vault_path,
+            SESSION_DOC_TYPE,
+            json!({ "mode": "Morning", "session_type": "morning", "metadata": {} }),
+            "not a transcript",
+        );
+        let missing_session = Uuid::new_v4();
+        let orphan = create(
+            vault_path,
+            ENTRY_DOC_TYPE,
+            json!({ "session_id": missing_session, "session_type": "morning" }),
+            "# Entry",
+        );
+        let unreadable = vault_path
+            .join("docs")
+            .join(format!("{}.md", Uuid::new_v4()));
+        fs::write(&unreadable, "---\nnot: [frontmatter\n---\n").unwrap();
+        fs::create_dir_all(vault_path.join(".aethel/indexes")).unwrap();
+        fs::write(vault_path.join(INDEX_FILE), "{").unwrap();
+        fs::write(
+            vault_path
+                .join(pack::pack_dir(pack::current_version()))
+                .join("pack.json"),
+            "{}",
+        )
+        .unwrap();
+
+        let problems = diagnose(vault_path).unwrap();
+        assert!(problems.contains(&Problem::CorruptIndex));
+        assert!(problems.contains(&Problem::SessionWithoutEntry(stopped)));
+        assert!(problems.contains(&Problem::EntryMissingSession {
+            entry_id: orphan,
+            session_id: missing_session,
+        }));
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            Problem::UnparseableTranscript { session_id, .. } if *session_id == garbled
+        )));
+        assert!(problems.iter().any(|problem| matches!(
+            problem,
+            Problem::SchemaViolation { doc_id, .. } if *doc_id == orphan
+        )));
+        assert!(problems.contains(&Problem::OutdatedPack(vec!["pack.json".to_string()])));
+        let unreadable_problem = problems
+            .iter()
+            .find(|problem| matches!(problem, Problem::UnreadableDoc { .. }))
+            .unwrap();
+        assert!(matches!(
+            unreadable_problem,
+            Problem::UnreadableDoc { path, .. } if vault_path.join(path) == unreadable
+        ));
+        assert!(fix(&runner, unreadable_problem).await.is_err());
+
+        for problem in problems
+            .iter()
+            .filter(|problem| *problem != unreadable_problem)
+        {
+            fix(&runner, problem).await.unwrap();
+        }
+        assert_eq!(
+            diagnose(vault_path).unwrap(),
+            vec![unreadable_problem.clone()]
+        );
+
+        let session = sessions::load_session(vault_path, stopped).unwrap();
+        assert_eq!(
+            session.metadata.status,
+            crate::state::SessionStatus::Archived
+        );
+        let entry = read_doc(vault_path, &session.metadata.final_entry_id.unwrap()).unwrap();
+        assert_eq!(entry.frontmatter_extra["analysis_status"], json!("pending"));
+
+        let garbled = sessions::load_session(vault_path, garbled).unwrap();
+        assert_eq!(garbled.transcript[0].content, "not a transcript");
+    }
+}
@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use uuid::Uuid;
+
+/// The user's editor: `$VISUAL`, then `$EDITOR`, falling back to `vi`
+fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Open the user's editor on a temp file holding `initial` and return what they saved
+pub fn compose(initial: &str) -> Result<String> {
+    let path = std::env::temp_dir().join(format!("journal-{}.md", Uuid::new_v4()));
+    fs::write(&path, initial).context("Failed to create temp file for the editor")?;
+
+    let result = run_editor(&editor_command(), &path);
+    let content = result.and_then(|_| {
+        fs::read_to_string(&path).context("Failed to read the file written by the editor")
+    });
+    // The draft may be private; never leave it behind in the temp directory
+    let _ = fs::remove_file(&path);
+
+    Ok(content?.trim().to_string())
+}
+
+/// Run `editor` on `path`. The editor may include arguments, e.g. `code --wait`.
+fn run_editor(editor: &str, path: &Path) -> Result<()> {
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().context("No editor configured")?;
+
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .with_context(|| format!("Failed to start editor '{program}'"))?;
+    if !status.success() {
+        anyhow::bail!("Editor '{program}' exited with {status}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_editor_passes_arguments_and_path() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let draft = temp_dir.path().join("draft.md");
+        let saved = temp_dir.path().join("saved.md");
+        fs::write(&draft, "").unwrap();
+        fs::write(&saved, "Written in the editor").unwrap();
+
+        // `cp <saved>` stands in for an editor that writes the file it's given
+        run_editor(&format!("cp {}", saved.display()), &draft).unwrap();
+        assert_eq!(fs::read_to_string(&draft).unwrap(), "Written in the editor");
+
+        assert!(run_editor("false", &draft).is_err());
+        assert!(run_editor("definitely_not_an_editor_12345", &draft).is_err());
+    }
+}
@@ -1,21 +1,30 @@
-use crate::state::{JournalSession, Speaker};
-use aethel_core::{apply_patch, read_doc, Patch, PatchMode};
+use crate::audit::{AuditLog, AuditRecord};
+use crate::backend::AiBackend;
+use crate::config::{JournalConfig, MentionsConfig};
+use crate::entries::{self, AnalysisVersion};
+use crate::highlights;
+use crate::index::IndexStore;
+use crate::mentions;
+use crate::pack;
+use crate::pending::{PendingAnalysis, PendingQueue};
+use crate::prompt::{self, Prompt};
+use crate::redact::{RedactionMap, Redactor};
+use crate::sessions;
+use crate::state::{AnalysisStatus, JournalSession, TranscriptSummary};
+use crate::tags;
+use aethel_core::{read_doc, Doc, Patch, PatchMode, WriteResult};
 use anyhow::{Context, Result};
 use chrono::Utc;
-use include_dir::{include_dir, Dir};
 use serde_json::json;
 use std::path::{Path, PathBuf};
-use std::process::Command;
-use tokio::fs;
+use std::process::Output;
 use uuid::Uuid;
 
-// Embed the journal pack assets at compile time
-static JOURNAL_PACK: Dir = include_dir!("assets/packs/journal@0.1.0");
-
 #[derive(Debug, Clone)]
 pub enum Effect {
     SaveSession(JournalSession),
     LoadSession(Uuid),
+    DeleteSession(Uuid),
     ClearIndex,
     RequestCoachResponse {
         session: JournalSession,
@@ -26,33 +35,74 @@ pub enum Effect {
     },
     CreateFinalEntry {
         session: JournalSession,
-        entry_id: Uuid,
         analysis: String,
+        analysis_status: AnalysisStatus,
     },
     InitializeVault(PathBuf),
 }
 
 pub struct EffectRunner {
     pub vault_path: PathBuf,
+    pub backend: AiBackend,
+    /// Refuse every effect that would contact the AI backend
+    pub local_only: bool,
+    redactor: Redactor,
+    mentions: MentionsConfig,
+    audit_log: AuditLog,
+    pending_queue: PendingQueue,
+}
+
+/// Outcome of retrying queued analyses
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PendingReport {
+    pub completed: usize,
+    pub remaining: usize,
 }
 
 impl EffectRunner {
     pub fn new(vault_path: PathBuf) -> Self {
-        Self { vault_path }
+        Self {
+            audit_log: AuditLog::new(&vault_path),
+            pending_queue: PendingQueue::new(&vault_path),
+            vault_path,
+            backend: AiBackend::default(),
+            local_only: false,
+            redactor: Redactor::default(),
+            mentions: MentionsConfig::default(),
+        }
+    }
+
+    pub fn with_config(vault_path: PathBuf, config: &JournalConfig) -> Result<Self> {
+        Ok(Self {
+            audit_log: AuditLog::new(&vault_path),
+            pending_queue: PendingQueue::new(&vault_path),
+            vault_path,
+            backend: AiBackend::default(),
+            local_only: false,
+            redactor: Redactor::from_config(&config.redaction)
+                .context("Failed to build redaction rules from config")?,
+            mentions: config.mentions.clone(),
+        })
     }
 
     pub async fn run_effect(&self, effect: Effect) -> Result<Option<crate::action::Action>> {
         match effect {
             Effect::SaveSession(session) => {
-                self.save_session(&session).await?;
-                Ok(None)
+                let session_id = self.save_session(&session).await?;
+                // Report the UUID of newly created session docs so later saves update the same doc
+                Ok(session
+                    .metadata
+                    .session_doc_id
+                    .is_none()
+                    .then_some(crate::action::Action::SessionSaved(session_id)))
             }
             Effect::LoadSession(session_id) => {
-                let _session = self.load_session(session_id).await?;
-                // Return action to transition to InSession state
-                Ok(Some(crate::action::Action::UserResponse(
-                    "session_loaded".to_string(),
-                )))
+                let session = self.load_session(session_id).await?;
+                Ok(Some(crate::action::Action::SessionLoaded(session)))
+            }
+            Effect::DeleteSession(session_id) => {
+                sessions::delete_session(&self.vault_path, session_id)?;
+                Ok(None)
             }
             Effect::ClearIndex => {
                 self.clear_index().await?;
@@ -62,26 +112,41 @@ impl EffectRunner {
                 session,
                 user_response,
             } => {
+                self.ensure_ai_allowed(&session)?;
+
+                // Fold older turns into the rolling summary first if the history is over budget
+                if let Some(through) = prompt::summary_cutoff(&session, self.backend.token_budget())
+                {
+                    let summary = self.summarize_transcript(&session, through).await?;
+                    return Ok(Some(crate::action::Action::TranscriptSummarized(summary)));
+                }
+
                 let response = self
                     .request_coach_response(&session, &user_response)
                     .await?;
                 Ok(Some(crate::action::Action::CoachResponse(response)))
             }
             Effect::GenerateAnalysis { session } => {
+                self.ensure_ai_allowed(&session)?;
                 let analysis = self.generate_analysis(&session).await?;
                 Ok(Some(crate::action::Action::AnalysisComplete(analysis)))
             }
             Effect::CreateFinalEntry {
                 session,
-                entry_id,
                 analysis,
+                analysis_status,
             } => {
-                let entry_path = self
-                    .create_final_entry(&session, entry_id, &analysis)
+                // Suggestions are a nicety; a vault that can't be scanned just gets none
+                let vocabulary = tags::vocabulary(&self.vault_path).unwrap_or_default();
+                let written = self
+                    .create_final_entry(&session, &analysis, analysis_status)
                     .await?;
                 Ok(Some(crate::action::Action::FinalEntryCreated {
-                    entry_path,
+                    entry_id: written.uuid,
+                    entry_path: written.path,
+                    suggested_tags: tags::suggest(&session, &analysis, &vocabulary),
                     analysis,
+                    analysis_status,
                 }))
             }
             Effect::InitializeVault(path) => {
@@ -91,21 +156,25 @@ impl EffectRunner {
         }
     }
 
-    fn ensure_vault_exists(&self) -> Result<()> {
-        // Check if vault exists, if not initialize it
-        if !self.vault_path.join(".aethel").exists() {
-            // Create basic vault structure
-            std::fs::create_dir_all(self.vault_path.join("docs"))
-                .context("Failed to create docs directory")?;
-            std::fs::create_dir_all(self.vault_path.join("packs"))
-                .context("Failed to create packs directory")?;
-            std::fs::create_dir_all(self.vault_path.join(".aethel"))
-                .context("Failed to create .aethel directory")?;
+    fn ensure_ai_allowed(&self, session: &JournalSession) -> Result<()> {
+        if self.local_only || session.metadata.private {
+            anyhow::bail!("Refusing to contact the AI backend: this session is private");
         }
         Ok(())
     }
 
-    async fn save_session(&self, session: &JournalSession) -> Result<()> {
+    fn ensure_vault_exists(&self) -> Result<()> {
+        // Create the basic vault structure if any of it is missing
+        std::fs::create_dir_all(self.vault_path.join("docs"))
+            .context("Failed to create docs directory")?;
+        std::fs::create_dir_all(self.vault_path.join("packs"))
+            .context("Failed to create packs directory")?;
+        std::fs::create_dir_all(self.vault_path.join(".aethel"))
+            .context("Failed to create .aethel directory")?;
+        Ok(())
+    }
+
+    async fn save_session(&self, session: &JournalSession) -> Result<Uuid> {
         self.ensure_vault_exists()?;
 
         // Create a copy of the metadata for the frontmatter
@@ -127,84 +196,29 @@ impl EffectRunner {
                     crate::state::SessionMode::Evening => "evening",
                 }
             })),
-            body: Some(
-                serde_json::to_string_pretty(&session.transcript)
-                    .context("Failed to serialize transcript")?,
-            ),
+            body: Some(sessions::transcript_body(session)?),
         };
 
-        let write_result =
-            apply_patch(&self.vault_path, patch).context("Failed to save session document")?;
+        let write_result = pack::apply_checked(&self.vault_path, patch)
+            .context("Failed to save session document")?;
 
         // Update the index to track this session as active
         self.update_index(write_result.uuid).await?;
 
-        Ok(())
+        Ok(write_result.uuid)
     }
 
     async fn load_session(&self, session_id: Uuid) -> Result<JournalSession> {
         self.ensure_vault_exists()?;
-
-        // Try to read the session document by UUID
-        let doc =
-            read_doc(&self.vault_path, &session_id).context("Failed to load session document")?;
-
-        // Parse the transcript from the body
-        let transcript =
-            serde_json::from_str(&doc.body).context("Failed to parse session transcript")?;
-
-        // Extract session data from frontmatter
-        let session_data = &doc.frontmatter_extra;
-        let session = JournalSession {
-            mode: serde_json::from_value(
-                session_data
-                    .get("mode")
-                    .cloned()
-                    .unwrap_or(json!("Morning")),
-            )
-            .unwrap_or(crate::state::SessionMode::Morning),
-            transcript,
-            metadata: serde_json::from_value(
-                session_data.get("metadata").cloned().unwrap_or(json!({})),
-            )
-            .context("Failed to parse session metadata")?,
-        };
-
-        Ok(session)
+        sessions::load_session(&self.vault_path, session_id)
     }
 
     async fn update_index(&self, session_id: Uuid) -> Result<()> {
-        let index_path = self.vault_path.join(".aethel/indexes/journal.index.json");
-
-        // Ensure the directory exists
-        if let Some(parent) = index_path.parent() {
-            fs::create_dir_all(parent)
-                .await
-                .context("Failed to create indexes directory")?;
-        }
-
-        let index_data = json!({
-            "active_session": session_id,
-            "updated_at": Utc::now()
-        });
-
-        fs::write(&index_path, serde_json::to_string_pretty(&index_data)?)
-            .await
-            .context("Failed to write index file")?;
-
-        Ok(())
+        IndexStore::new(&self.vault_path).set_active(session_id)
     }
 
     async fn clear_index(&self) -> Result<()> {
-        let index_path = self.vault_path.join(".aethel/indexes/journal.index.json");
-
-        if index_path.exists() {
-            fs::remove_file(&index_path)
-                .await
-                .context("Failed to remove index file")?;
-        }
-
-        Ok(())
+        IndexStore::new(&self.vault_path).clear()
     }
 
     async fn request_coach_response(
@@ -212,32 +226,12 @@ impl EffectRunner {
         session: &JournalSession,
         user_response: &str,
     ) -> Result<String> {
-        let context = session.mode.get_coaching_context();
-        let conversation_history = session
-            .transcript
-            .iter()
-            .filter(|entry| matches!(entry.speaker, Speaker::User | Speaker::Coach))
-            .map(|entry| {
-                let role = match entry.speaker {
-                    Speaker::User => "user",
-                    Speaker::Coach => "assistant",
-                    _ => "system",
-                };
-                format!("{}: {}", role, entry.content)
-            })
-            .collect::<Vec<_>>()
-            .join("\n");
+        let mut redactions = RedactionMap::default();
+        let redacted = self.redact_session(session, &mut redactions);
+        let user_response = self.redactor.redact(user_response, &mut redactions);
+        let prompt = prompt::coach_prompt(&redacted, &user_response, self.backend.token_budget());
 
-        let prompt = format!(
-            "{context}\n\nConversation so far:\n{conversation_history}\n\nLatest user response: {user_response}\n\nPlease respond as an empathetic coach with a follow-up question or reflection that helps deepen their self-awareness."
-        );
-
-        // Call claude CLI as subprocess
-        let output = Command::new("claude")
-            .arg("-p")
-            .arg(&prompt)
-            .output()
-            .context("Failed to execute claude command")?;
+        let output = self.send_prompt(&prompt, session.metadata.session_doc_id, &redactions)?;
 
         if !output.status.success() {
             let stderr = String::from_utf8_lossy(&output.stderr);
@@ -249,33 +243,49 @@ impl EffectRunner {
             .trim()
             .to_string();
 
-        Ok(response)
+        Ok(redactions.restore(&response))
     }
 
-    async fn generate_analysis(&self, session: &JournalSession) -> Result<String> {
-        let context = match session.mode {
-            crate::state::SessionMode::Morning => "morning reflections and intentions",
-            crate::state::SessionMode::Evening => "evening reflections and insights",
-        };
+    async fn summarize_transcript(
+        &self,
+        session: &JournalSession,
+        through: usize,
+    ) -> Result<TranscriptSummary> {
+        let mut redactions = RedactionMap::default();
+        let redacted = self.redact_session(session, &mut redactions);
+        let prompt = prompt::summarization_prompt(&redacted, through);
 
-        let conversation_summary = session.get_conversation_summary();
-
-        let prompt = format!(
-            "Please analyze this {context} journal session and provide:\n\n\
-            1. **Key Insights**: What are the main themes and patterns you notice?\n\
-            2. **Emotional Journey**: How did the person's emotional state evolve?\n\
-            3. **Action Items**: What specific, actionable steps could they take based on this session?\n\
-            4. **Reflections**: What deeper questions or areas for future exploration emerged?\n\
-            5. **Summary**: A brief 2-3 sentence summary of the session\n\n\
-            Journal Session:\n{conversation_summary}\n\n\
-            Provide a thoughtful, empathetic analysis that honors their vulnerability and supports their growth."
-        );
+        let output = self
+            .send_prompt(&prompt, session.metadata.session_doc_id, &redactions)
+            .context("Failed to summarize transcript")?;
 
-        // Call claude CLI as subprocess for analysis
-        let output = Command::new("claude")
-            .arg("-p")
-            .arg(&prompt)
-            .output()
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Claude summarization command failed: {}", stderr);
+        }
+
+        let summary = String::from_utf8(output.stdout)
+            .context("Invalid UTF-8 in claude summary")?
+            .trim()
+            .to_string();
+
+        if summary.is_empty() {
+            anyhow::bail!("Claude command succeeded but returned empty summary");
+        }
+
+        Ok(TranscriptSummary {
+            summary: redactions.restore(&summary),
+            entries_covered: through,
+        })
+    }
+
+    async fn generate_analysis(&self, session: &JournalSession) -> Result<String> {
+        let mut redactions = RedactionMap::default();
+        let redacted = self.redact_session(session, &mut redactions);
+        let prompt = prompt::analysis_prompt(&redacted, self.backend.token_budget());
+
+        let output = self
+            .send_prompt(&prompt, session.metadata.session_doc_id, &redactions)
             .context(
                 "Failed to execute claude command for analysis - is 'claude' CLI installed?",
             )?;
@@ -317,17 +327,176 @@ impl EffectRunner {
             );
         }
 
-        Ok(analysis)
+        Ok(redactions.restore(&analysis))
+    }
+
+    /// Ask the backend for the narrative synthesis of a periodic digest
+    pub async fn synthesize_digest(
+        &self,
+        period: &str,
+        stats: &str,
+        material: &str,
+    ) -> Result<String> {
+        if self.local_only {
+            anyhow::bail!("Refusing to contact the AI backend in local-only mode");
+        }
+
+        let mut redactions = RedactionMap::default();
+        let prompt = prompt::digest_prompt(
+            period,
+            &self.redactor.redact(stats, &mut redactions),
+            &self.redactor.redact(material, &mut redactions),
+            self.backend.token_budget(),
+        );
+        let output = self
+            .send_prompt(&prompt, None, &redactions)
+            .context("Failed to request digest synthesis")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Claude digest command failed: {}", stderr);
+        }
+
+        let synthesis = String::from_utf8(output.stdout)
+            .context("Invalid UTF-8 in claude digest response")?
+            .trim()
+            .to_string();
+
+        if synthesis.is_empty() {
+            anyhow::bail!("Claude command succeeded but returned empty digest synthesis");
+        }
+
+        Ok(redactions.restore(&synthesis))
+    }
+
+    /// People, places and projects the session mentions. When configured, the AI backend is
+    /// asked too for sessions it analyzed; if that fails the local extraction stands.
+    fn find_mentions(
+        &self,
+        session: &JournalSession,
+        analysis_status: AnalysisStatus,
+    ) -> Vec<String> {
+        let responses = session.get_user_responses();
+        let mut found = mentions::extract(
+            responses.iter().flat_map(|entry| entry.content.lines()),
+            &self.mentions,
+        );
+
+        let ask_backend = self.mentions.ai_extraction
+            && analysis_status == AnalysisStatus::Complete
+            && self.ensure_ai_allowed(session).is_ok();
+        if ask_backend {
+            for name in self.extract_mentions(session).unwrap_or_default() {
+                let name = mentions::canonical(&name, &self.mentions);
+                if !found.contains(&name) {
+                    found.push(name);
+                }
+            }
+        }
+        found
+    }
+
+    fn extract_mentions(&self, session: &JournalSession) -> Result<Vec<String>> {
+        let mut redactions = RedactionMap::default();
+        let redacted = self.redact_session(session, &mut redactions);
+        let output = self.send_prompt(
+            &prompt::mentions_prompt(&redacted, self.backend.token_budget()),
+            session.metadata.session_doc_id,
+            &redactions,
+        )?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("Claude mentions command failed: {}", stderr);
+        }
+
+        let response = redactions.restore(
+            String::from_utf8(output.stdout)
+                .context("Invalid UTF-8 in claude mentions response")?
+                .trim(),
+        );
+        Ok(response
+            .lines()
+            .map(|line| line.trim_start_matches(['-', '*', '•', ' ']).trim())
+            // Unrestored placeholders like "[Person A]" aren't names
+            .filter(|name| !name.is_empty() && !name.eq_ignore_ascii_case("none"))
+            .filter(|name| !name.contains('['))
+            .map(str::to_string)
+            .collect())
     }
 
-    async fn create_final_entry(
+    /// A copy of the session with everything the user wrote or was shown redacted. Prompts are
+    /// built from it, so configured names and patterns never rewrite the templates' own wording.
+    /// `redactions` collects the replacements to restore in the response.
+    fn redact_session(
+        &self,
+        session: &JournalSession,
+        redactions: &mut RedactionMap,
+    ) -> JournalSession {
+        let mut redacted = session.clone();
+        for entry in &mut redacted.transcript {
+            entry.content = self.redactor.redact(&entry.content, redactions);
+        }
+        if let Some(summary) = &mut redacted.metadata.transcript_summary {
+            summary.summary = self.redactor.redact(&summary.summary, redactions);
+        }
+        for entry in &mut redacted.metadata.resurfaced {
+            entry.snippet = self.redactor.redact(&entry.snippet, redactions);
+        }
+        redacted
+    }
+
+    /// Send a prompt built from redacted text to the backend, recording the exchange in the
+    /// audit log. `redactions` are the replacements made in it, which the caller restores in
+    /// the response before it is displayed or stored.
+    fn send_prompt(
+        &self,
+        prompt: &Prompt,
+        session_id: Option<Uuid>,
+        redactions: &RedactionMap,
+    ) -> Result<Output> {
+        // Refuse to send anything that can't be recorded
+        let sent = AuditRecord::sent(
+            Uuid::new_v4(),
+            session_id,
+            prompt.kind,
+            self.backend.name(),
+            &prompt.template_hash,
+            &prompt.text,
+            redactions,
+        );
+        self.audit_log.append(&sent)?;
+
+        let output = match self.backend.invoke(&prompt.text) {
+            Ok(output) => output,
+            Err(e) => {
+                self.audit_log
+                    .append(&AuditRecord::failed(&sent, &format!("{e:#}")))?;
+                return Err(e);
+            }
+        };
+
+        let response = String::from_utf8_lossy(&output.stdout);
+        self.audit_log.append(&AuditRecord::received(
+            &sent,
+            &response,
+            output.status.code(),
+        ))?;
+
+        Ok(output)
+    }
+
+    /// Write the entry doc for a finished session. The result's path is absolute.
+    pub async fn create_final_entry(
         &self,
         session: &JournalSession,
-        _entry_id: Uuid,
         analysis: &str,
-    ) -> Result<String> {
+        analysis_status: AnalysisStatus,
+    ) -> Result<WriteResult> {
         self.ensure_vault_exists()?;
 
+        let mood = extract_mood_from_session(session);
+        let energy = extract_energy_from_session(session);
+        let mentions = self.find_mentions(session, analysis_status);
         let frontmatter = json!({
             "session_id": session.metadata.session_doc_id,
             "mode": session.mode,
@@ -342,34 +511,185 @@ impl EffectRunner {
                 },
                 chrono::Utc::now().format("%Y-%m-%d")
             ),
-            "mood": extract_mood_from_session(session),
-            "energy": extract_energy_from_session(session)
+            "mood_score": mood.as_deref().and_then(entries::mood_value),
+            "energy_score": energy.as_deref().and_then(entries::energy_value),
+            "mood": mood,
+            "energy": energy,
+            // Only an analysis that came back means the backend has seen the session
+            "ai_processed": analysis_status == AnalysisStatus::Complete,
+            "tags": session.metadata.tags,
+            "mentions": mentions,
+            "highlights": highlights::from_session(session),
+            "analysis_status": analysis_status,
+            "analysis_versions": if analysis_status == AnalysisStatus::Complete {
+                vec![self.analysis_version(session, 1)]
+            } else {
+                Vec::new()
+            }
         });
 
-        let body = format!(
-            "# {} Journal Entry\n\n## Session Transcript\n\n{}\n\n## AI Analysis\n\n{}",
-            match session.mode {
-                crate::state::SessionMode::Morning => "Morning",
-                crate::state::SessionMode::Evening => "Evening",
-            },
-            session.get_conversation_summary(),
-            analysis
-        );
-
         let patch = Patch {
             uuid: None, // Create mode requires uuid to be None
             doc_type: Some("journal.entry".to_string()),
             mode: PatchMode::Create,
             frontmatter: Some(frontmatter),
+            body: Some(entry_body(session, analysis, analysis_status)),
+        };
+
+        let mut write_result = pack::apply_checked(&self.vault_path, patch)
+            .context("Failed to create final journal entry")?;
+        write_result.path = std::path::absolute(self.vault_path.join(&write_result.path))
+            .context("Failed to resolve the entry path")?;
+
+        // Remember the session so the analysis can be filled in once the backend is reachable.
+        // Private and local-only sessions are never sent, so there's nothing to wait for.
+        if analysis_status == AnalysisStatus::Pending && self.ensure_ai_allowed(session).is_ok() {
+            self.pending_queue
+                .push(PendingAnalysis::new(write_result.uuid, session.clone()))
+                .context("Failed to queue the analysis for later")?;
+        }
+
+        Ok(write_result)
+    }
+
+    /// Retry every queued analysis, filling in the entries that succeed. Failed attempts
+    /// stay queued with their error.
+    pub async fn complete_pending_analyses(&self) -> Result<PendingReport> {
+        let queued = self.pending_queue.load()?;
+        if queued.is_empty() {
+            return Ok(PendingReport::default());
+        }
+
+        let mut report = PendingReport::default();
+        let mut remaining = Vec::new();
+        for mut pending in queued {
+            if !self.may_send_later(&pending) {
+                continue;
+            }
+            match self.complete_pending_analysis(&pending).await {
+                Ok(()) => report.completed += 1,
+                Err(e) => {
+                    pending.attempts += 1;
+                    pending.last_error = Some(format!("{e:#}"));
+                    remaining.push(pending);
+                }
+            }
+        }
+
+        report.remaining = remaining.len();
+        self.pending_queue.save(&remaining)?;
+        Ok(report)
+    }
+
+    /// Whether a queued analysis may still be sent. Ones whose session was private, or whose
+    /// entry was written with a local summary, are dropped from the queue however they got in.
+    fn may_send_later(&self, pending: &PendingAnalysis) -> bool {
+        let entry_status: Option<AnalysisStatus> = read_doc(&self.vault_path, &pending.entry_id)
+            .ok()
+            .and_then(|entry| entry.frontmatter_extra.get("analysis_status").cloned())
+            .and_then(|status| serde_json::from_value(status).ok());
+        !pending.session.metadata.private && entry_status != Some(AnalysisStatus::Local)
+    }
+
+    async fn complete_pending_analysis(&self, pending: &PendingAnalysis) -> Result<()> {
+        self.ensure_ai_allowed(&pending.session)?;
+        let analysis = self.generate_analysis(&pending.session).await?;
+
+        let patch = Patch {
+            uuid: Some(pending.entry_id),
+            doc_type: Some("journal.entry".to_string()),
+            mode: PatchMode::MergeFrontmatter,
+            frontmatter: Some(json!({
+                "analysis_status": AnalysisStatus::Complete,
+                "ai_processed": true,
+                "analysis_versions": [self.analysis_version(&pending.session, 1)]
+            })),
+            body: Some(entry_body(
+                &pending.session,
+                &analysis,
+                AnalysisStatus::Complete,
+            )),
+        };
+
+        pack::apply_checked(&self.vault_path, patch)
+            .with_context(|| format!("Failed to add analysis to entry {}", pending.entry_id))?;
+        Ok(())
+    }
+
+    /// Regenerate the analysis of an existing entry from its linked session with the current
+    /// template and backend. The previous analysis is kept in the entry as an older version.
+    pub async fn reanalyze_entry(&self, entry: &Doc) -> Result<AnalysisVersion> {
+        let session_id = entry
+            .frontmatter_extra
+            .get("session_id")
+            .and_then(|id| id.as_str())
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .context("Entry is not linked to a journal session")?;
+        let status = entry
+            .frontmatter_extra
+            .get("analysis_status")
+            .cloned()
+            .and_then(|status| serde_json::from_value(status).ok());
+        if status == Some(AnalysisStatus::Local) {
+            anyhow::bail!("Refusing to contact the AI backend: this entry is private");
+        }
+
+        let session = self.load_session(session_id).await?;
+        self.ensure_ai_allowed(&session)?;
+        let analysis = self.generate_analysis(&session).await?;
+
+        let mut versions = entries::analysis_versions(entry);
+        let (current, older) = entries::split_analysis_sections(&entry.body, "AI Analysis");
+        // A pending placeholder is replaced outright rather than kept as a version
+        let keep_current = status != Some(AnalysisStatus::Pending) && !current.is_empty();
+        let next_version = match versions.last() {
+            Some(latest) => latest.version + 1,
+            None if keep_current => 2,
+            None => 1,
+        };
+
+        let mut body = entry_body(&session, &analysis, AnalysisStatus::Complete);
+        if keep_current {
+            body.push_str("\n\n");
+            body.push_str(&entries::previous_analyses_section(
+                current,
+                versions.last(),
+                older,
+            ));
+        }
+
+        let version = self.analysis_version(&session, next_version);
+        versions.push(version.clone());
+
+        let patch = Patch {
+            uuid: Some(entry.uuid),
+            doc_type: Some(entries::ENTRY_DOC_TYPE.to_string()),
+            mode: PatchMode::MergeFrontmatter,
+            frontmatter: Some(json!({
+                "analysis_status": AnalysisStatus::Complete,
+                "ai_processed": true,
+                "analysis_versions": versions
+            })),
             body: Some(body),
         };
+        pack::apply_checked(&self.vault_path, patch)
+            .with_context(|| format!("Failed to update entry {}", entry.uuid))?;
+
+        // The entry now has an analysis, so any queued retry is obsolete
+        self.pending_queue.remove(entry.uuid)?;
 
-        let write_result =
-            apply_patch(&self.vault_path, patch).context("Failed to create final journal entry")?;
+        Ok(version)
+    }
 
-        // Return the entry path
-        let entry_path = format!("docs/{}.md", write_result.uuid);
-        Ok(entry_path)
+    /// Provenance of an analysis generated now for `session`
+    fn analysis_version(&self, session: &JournalSession, version: u32) -> AnalysisVersion {
+        AnalysisVersion {
+            version,
+            generated_at: Utc::now(),
+            backend: self.backend.name().to_string(),
+            template_hash: prompt::analysis_prompt(session, self.backend.token_budget())
+                .template_hash,
+        }
     }
 
     async fn initialize_vault(&self, path: &Path) -> Result<()> {
@@ -380,26 +700,28 @@ impl EffectRunner {
             .context("Failed to create .aethel directory")?;
 
         // Install the journal pack
-        self.install_journal_pack(path).await?;
-
-        Ok(())
+        pack::install(path)
     }
+}
 
-    async fn install_journal_pack(&self, vault_path: &Path) -> Result<()> {
-        let pack_path = vault_path.join("packs/journal@0.1.0");
-
-        // Create the pack directory first
-        fs::create_dir_all(&pack_path)
-            .await
-            .context("Failed to create pack directory")?;
-
-        // Extract all files from the embedded pack directory
-        JOURNAL_PACK
-            .extract(&pack_path)
-            .context("Failed to extract journal pack files")?;
-
-        Ok(())
-    }
+/// Markdown body of a journal entry: the transcript followed by its analysis section
+fn entry_body(session: &JournalSession, analysis: &str, analysis_status: AnalysisStatus) -> String {
+    // Private sessions carry a locally computed summary rather than an AI analysis
+    let analysis_heading = match analysis_status {
+        AnalysisStatus::Local => "Session Statistics",
+        AnalysisStatus::Complete | AnalysisStatus::Pending => "AI Analysis",
+    };
+
+    format!(
+        "# {} Journal Entry\n\n## Session Transcript\n\n{}\n\n## {}\n\n{}",
+        match session.mode {
+            crate::state::SessionMode::Morning => "Morning",
+            crate::state::SessionMode::Evening => "Evening",
+        },
+        session.get_conversation_summary(),
+        analysis_heading,
+        analysis
+    )
 }
 
 fn extract_mood_from_session(session: &JournalSession) -> Option<String> {
@@ -447,7 +769,9 @@ fn extract_energy_from_session(session: &JournalSession) -> Option<String> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state::{JournalSession, SessionMode, Speaker};
+    use crate::action::Action;
+    use crate::state::{JournalSession, ResurfacedEntry, SessionMode, SessionStatus, Speaker};
+    use std::process::Command;
     use tempfile::TempDir;
 
     #[tokio::test]
@@ -605,6 +929,264 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_redaction_leaves_prompt_templates_alone() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut config = JournalConfig::default();
+        config.redaction.names = vec!["Sarah".to_string(), "coach".to_string()];
+        let runner = EffectRunner::with_config(temp_dir.path().to_path_buf(), &config).unwrap();
+
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::User, "Sarah is my running coach.".to_string());
+        let mut redactions = RedactionMap::default();
+        let redacted = runner.redact_session(&session, &mut redactions);
+        let user_response = runner
+            .redactor
+            .redact("Sarah is my running coach.", &mut redactions);
+        let prompt = prompt::coach_prompt(&redacted, &user_response, 10_000);
+
+        // The configured name is replaced in what the user wrote, not in the instructions
+        assert!(prompt
+            .text
+            .contains("Latest user response: [Person A] is my running [Person B]."));
+        assert!(prompt.text.contains("respond as an empathetic coach"));
+        assert!(!prompt.text.contains("Sarah"));
+        assert_eq!(
+            redactions.restore("Say hi to [Person A]"),
+            "Say hi to Sarah"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_private_session_refuses_ai_effects() {
+        let temp_dir = TempDir::new().unwrap();
+        let effect_runner = EffectRunner::new(temp_dir.path().to_path_buf());
+
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.metadata.private = true;
+        session.add_entry(Speaker::User, "Something personal".to_string());
+
+        let coach = effect_runner
+            .run_effect(Effect::RequestCoachResponse {
+                session: session.clone(),
+                user_response: "Something personal".to_string(),
+            })
+            .await;
+        assert!(coach.unwrap_err().to_string().contains("private"));
+
+        let analysis = effect_runner
+            .run_effect(Effect::GenerateAnalysis { session })
+            .await;
+        assert!(analysis.unwrap_err().to_string().contains("private"));
+
+        // Nothing was attempted, so nothing was audited
+        assert!(!temp_dir.path().join(crate::audit::AUDIT_LOG_FILE).exists());
+    }
+
+    #[tokio::test]
+    async fn test_local_only_runner_refuses_ai_effects() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut effect_runner = EffectRunner::new(temp_dir.path().to_path_buf());
+        effect_runner.local_only = true;
+
+        let session = JournalSession::new(SessionMode::Morning);
+        let result = effect_runner
+            .run_effect(Effect::GenerateAnalysis { session })
+            .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_private_entry_is_marked_not_ai_processed() {
+        let temp_dir = TempDir::new().unwrap();
+        let effect_runner = EffectRunner::new(temp_dir.path().to_path_buf());
+
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.metadata.private = true;
+        session.add_entry(Speaker::User, "A quiet evening".to_string());
+
+        let written = effect_runner
+            .create_final_entry(&session, "local summary", AnalysisStatus::Local)
+            .await
+            .unwrap();
+        assert!(written.path.is_absolute());
+        assert!(written.path.ends_with(format!("docs/{}.md", written.uuid)));
+
+        let doc = read_doc(temp_dir.path(), &written.uuid).unwrap();
+        assert_eq!(
+            doc.frontmatter_extra.get("ai_processed"),
+            Some(&json!(false))
+        );
+        assert!(doc.body.contains("## Session Statistics"));
+        assert!(!doc.body.contains("## AI Analysis"));
+    }
+
+    #[tokio::test]
+    async fn test_pending_entry_is_queued_until_analyzed() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut effect_runner = EffectRunner::new(temp_dir.path().to_path_buf());
+
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::User, "A long day".to_string());
+
+        let written = effect_runner
+            .create_final_entry(&session, "analysis pending", AnalysisStatus::Pending)
+            .await
+            .unwrap();
+
+        let queued = PendingQueue::new(temp_dir.path()).load().unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].entry_id, written.uuid);
+
+        let doc = read_doc(temp_dir.path(), &queued[0].entry_id).unwrap();
+        assert_eq!(
+            doc.frontmatter_extra.get("analysis_status"),
+            Some(&json!("pending"))
+        );
+        assert_eq!(
+            doc.frontmatter_extra.get("ai_processed"),
+            Some(&json!(false))
+        );
+        assert!(entries::shareable_with_ai(&doc));
+
+        // A failed retry keeps the analysis queued and records why
+        effect_runner.local_only = true;
+        let report = effect_runner.complete_pending_analyses().await.unwrap();
+        assert_eq!(
+            report,
+            PendingReport {
+                completed: 0,
+                remaining: 1
+            }
+        );
+        let queued = PendingQueue::new(temp_dir.path()).load().unwrap();
+        assert_eq!(queued[0].attempts, 1);
+        assert!(queued[0].last_error.as_deref().unwrap().contains("private"));
+    }
+
+    #[tokio::test]
+    async fn test_private_and_local_only_sessions_are_never_queued() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut effect_runner = EffectRunner::new(temp_dir.path().to_path_buf());
+        let queue = PendingQueue::new(temp_dir.path());
+
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::User, "Just for me".to_string());
+        effect_runner.local_only = true;
+        effect_runner
+            .create_final_entry(&session, "analysis pending", AnalysisStatus::Pending)
+            .await
+            .unwrap();
+        assert!(queue.load().unwrap().is_empty());
+
+        // A private session that is queued anyway is dropped without contacting the backend
+        session.metadata.private = true;
+        effect_runner.local_only = false;
+        queue
+            .push(PendingAnalysis::new(Uuid::new_v4(), session))
+            .unwrap();
+        let report = effect_runner.complete_pending_analyses().await.unwrap();
+        assert_eq!(report, PendingReport::default());
+        assert!(queue.load().unwrap().is_empty());
+        assert!(AuditLog::new(temp_dir.path()).records().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reanalyze_refuses_private_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let effect_runner = EffectRunner::new(temp_dir.path().to_path_buf());
+
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.metadata.private = true;
+        let session_id = effect_runner.save_session(&session).await.unwrap();
+        session.metadata.session_doc_id = Some(session_id);
+
+        let written = effect_runner
+            .create_final_entry(&session, "local", AnalysisStatus::Local)
+            .await
+            .unwrap();
+        let entries = entries::find_entries(temp_dir.path(), &Default::default()).unwrap();
+        assert_eq!(entries[0].uuid, written.uuid);
+
+        let result = effect_runner.reanalyze_entry(&entries[0]).await;
+        assert!(result.unwrap_err().to_string().contains("private"));
+        assert!(!temp_dir.path().join(crate::audit::AUDIT_LOG_FILE).exists());
+    }
+
+    #[tokio::test]
+    async fn test_effect_outputs_match_pack_schemas() {
+        let temp_dir = TempDir::new().unwrap();
+        let effect_runner = EffectRunner::new(temp_dir.path().to_path_buf());
+        let assert_valid = |uuid: Uuid| {
+            let doc = read_doc(temp_dir.path(), &uuid).unwrap();
+            let frontmatter = serde_json::Value::Object(doc.frontmatter_extra);
+            let violations = pack::validate(&doc.doc_type, &frontmatter).unwrap();
+            assert!(violations.is_empty(), "{}: {violations:?}", doc.doc_type);
+        };
+
+        let mut session = JournalSession::new(SessionMode::Morning);
+        let Some(Action::SessionSaved(session_id)) = effect_runner
+            .run_effect(Effect::SaveSession(session.clone()))
+            .await
+            .unwrap()
+        else {
+            panic!("expected SessionSaved");
+        };
+        assert_valid(session_id);
+
+        // Fill in every optional part of the session metadata
+        session.metadata.session_doc_id = Some(session_id);
+        session.add_entry(Speaker::User, "Slept well".to_string());
+        session.add_entry(Speaker::Coach, "What made the difference?".to_string());
+        session.retract_last_response();
+        session.add_entry(Speaker::Coach, "What's on today?".to_string());
+        session.star_latest(&Speaker::User);
+        session.metadata.tags = vec!["sleep".to_string()];
+        session.metadata.transcript_summary = Some(TranscriptSummary {
+            summary: "Slept well".to_string(),
+            entries_covered: 1,
+        });
+        session.metadata.resurfaced = vec![ResurfacedEntry {
+            entry_id: Uuid::new_v4(),
+            label: "One week ago".to_string(),
+            date: chrono::Local::now().date_naive(),
+            snippet: "Tired".to_string(),
+        }];
+        session.metadata.status = SessionStatus::Paused;
+        effect_runner
+            .run_effect(Effect::SaveSession(session.clone()))
+            .await
+            .unwrap();
+        assert_valid(session_id);
+
+        session.mark_completed();
+        for analysis_status in [
+            AnalysisStatus::Complete,
+            AnalysisStatus::Pending,
+            AnalysisStatus::Local,
+        ] {
+            let Some(Action::FinalEntryCreated { entry_id, .. }) = effect_runner
+                .run_effect(Effect::CreateFinalEntry {
+                    session: session.clone(),
+                    analysis: "analysis".to_string(),
+                    analysis_status,
+                })
+                .await
+                .unwrap()
+            else {
+                panic!("expected FinalEntryCreated");
+            };
+            assert_valid(entry_id);
+            session.archive(entry_id);
+        }
+
+        effect_runner
+            .run_effect(Effect::SaveSession(session))
+            .await
+            .unwrap();
+        assert_valid(session_id);
+    }
+
     #[tokio::test]
     async fn test_execution_error_handling() {
         // Test what happens when we simulate the "Execution error" response
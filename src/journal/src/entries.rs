@@ -0,0 +1,410 @@
+use crate::state::{AnalysisStatus, SessionMode, TranscriptEntry};
+use aethel_core::{read_doc, Doc};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use uuid::Uuid;
+
+pub const ENTRY_DOC_TYPE: &str = "journal.entry";
+
+/// Common words that never count as themes
+const STOP_WORDS: &[&str] = &[
+    "about", "after", "again", "also", "because", "been", "before", "being", "could", "didn't",
+    "doing", "don't", "from", "going", "have", "just", "like", "more", "much", "really", "some",
+    "that", "their", "them", "then", "there", "they", "thing", "things", "think", "this", "today",
+    "very", "want", "were", "what", "when", "which", "with", "would", "your",
+];
+
+/// Heading of the section holding superseded analyses in an entry body
+const PREVIOUS_ANALYSES_HEADING: &str = "## Previous Analyses";
+
+/// Provenance of one analysis stored in an entry, recorded in its `analysis_versions`
+/// frontmatter (oldest first)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AnalysisVersion {
+    pub version: u32,
+    pub generated_at: DateTime<Utc>,
+    pub backend: String,
+    pub template_hash: String,
+}
+
+/// Which entries a command should operate on
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EntryFilter {
+    /// Only entries created on or after this (local) date
+    pub since: Option<NaiveDate>,
+    /// Only entries created on or before this (local) date
+    pub until: Option<NaiveDate>,
+    /// Only entries whose text contains this, case-insensitively
+    pub query: Option<String>,
+    /// Only entries with this tag
+    pub tag: Option<String>,
+    /// Only entries from sessions in this mode
+    pub mode: Option<SessionMode>,
+}
+
+impl EntryFilter {
+    pub fn matches(&self, doc: &Doc) -> bool {
+        let after_since = self.since.is_none_or(|since| entry_date(doc) >= since);
+        let before_until = self.until.is_none_or(|until| entry_date(doc) <= until);
+        let matches_query = self.query.as_ref().is_none_or(|query| {
+            let query = query.to_lowercase();
+            doc.body.to_lowercase().contains(&query)
+                || doc
+                    .frontmatter_extra
+                    .get("title")
+                    .and_then(|title| title.as_str())
+                    .is_some_and(|title| title.to_lowercase().contains(&query))
+        });
+
+        let has_tag = self.tag.as_ref().is_none_or(|tag| doc.tags.contains(tag));
+        let in_mode = self.mode.is_none_or(|mode| {
+            doc.frontmatter_extra.get("mode").cloned()
+                == Some(serde_json::to_value(mode).unwrap_or_default())
+        });
+
+        after_since && before_until && matches_query && has_tag && in_mode
+    }
+}
+
+/// All journal entries in the vault matching `filter`, oldest first
+pub fn find_entries(vault_path: &Path, filter: &EntryFilter) -> Result<Vec<Doc>> {
+    let docs_dir = vault_path.join("docs");
+    if !docs_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut entries = Vec::new();
+    for dir_entry in std::fs::read_dir(&docs_dir).context("Failed to read docs directory")? {
+        let path = dir_entry?.path();
+        let Some(uuid) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| Uuid::parse_str(stem).ok())
+        else {
+            continue;
+        };
+
+        // Skip docs that can't be read rather than failing the whole scan
+        let Ok(doc) = read_doc(vault_path, &uuid) else {
+            continue;
+        };
+        if doc.doc_type == ENTRY_DOC_TYPE && filter.matches(&doc) {
+            entries.push(doc);
+        }
+    }
+
+    entries.sort_by_key(|doc| doc.created);
+    Ok(entries)
+}
+
+/// What the user wrote in an entry's transcript section. A turn runs from its `You: ` line
+/// until the next speaker or section heading; blank lines between paragraphs are skipped.
+pub fn user_lines(body: &str) -> impl Iterator<Item = &str> {
+    body.lines()
+        .scan(false, |in_user_turn, line| {
+            if let Some(first) = line.strip_prefix("You: ") {
+                *in_user_turn = true;
+                return Some(Some(first));
+            }
+            if ["Coach: ", "System: ", "## "]
+                .iter()
+                .any(|heading| line.starts_with(heading))
+            {
+                *in_user_turn = false;
+            }
+            Some(Some(line).filter(|line| *in_user_turn && !line.trim().is_empty()))
+        })
+        .flatten()
+}
+
+/// Read one entry doc, failing if the doc is some other type
+pub fn read_entry(vault_path: &Path, entry_id: Uuid) -> Result<Doc> {
+    let entry = read_doc(vault_path, &entry_id).context("Failed to read entry")?;
+    if entry.doc_type != ENTRY_DOC_TYPE {
+        anyhow::bail!("{entry_id} is not a journal entry");
+    }
+    Ok(entry)
+}
+
+pub fn entry_title(entry: &Doc) -> String {
+    entry
+        .frontmatter_extra
+        .get("title")
+        .and_then(|title| title.as_str())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("Entry from {}", entry.created.format("%Y-%m-%d")))
+}
+
+/// The local calendar date an entry was written on
+pub fn entry_date(entry: &Doc) -> NaiveDate {
+    entry.created.with_timezone(&Local).date_naive()
+}
+
+/// Whether an entry's content may be sent to the AI backend
+pub fn shareable_with_ai(entry: &Doc) -> bool {
+    let status = entry
+        .frontmatter_extra
+        .get("analysis_status")
+        .cloned()
+        .and_then(|status| serde_json::from_value(status).ok());
+    let ai_processed = entry
+        .frontmatter_extra
+        .get("ai_processed")
+        .and_then(|value| value.as_bool())
+        .unwrap_or(true);
+
+    // Pending entries haven't reached the backend yet, but weren't written privately either
+    (ai_processed || status == Some(AnalysisStatus::Pending))
+        && status != Some(AnalysisStatus::Local)
+}
+
+/// The transcript of the session an entry was written from, with its timestamps
+pub fn linked_transcript(vault_path: &Path, entry: &Doc) -> Option<Vec<TranscriptEntry>> {
+    let session_id = entry
+        .frontmatter_extra
+        .get("session_id")?
+        .as_str()
+        .and_then(|id| Uuid::parse_str(id).ok())?;
+    let session = read_doc(vault_path, &session_id).ok()?;
+    serde_json::from_str(&session.body).ok()
+}
+
+/// Detected mood from 1 (challenging) to 3 (positive)
+pub fn mood_score(entry: &Doc) -> Option<f64> {
+    let frontmatter = &entry.frontmatter_extra;
+    frontmatter
+        .get("mood_score")
+        .and_then(Value::as_f64)
+        .or_else(|| mood_value(frontmatter.get("mood")?.as_str()?))
+}
+
+/// Detected energy from 1 (low) to 3 (high)
+pub fn energy_score(entry: &Doc) -> Option<f64> {
+    let frontmatter = &entry.frontmatter_extra;
+    frontmatter
+        .get("energy_score")
+        .and_then(Value::as_f64)
+        .or_else(|| energy_value(frontmatter.get("energy")?.as_str()?))
+}
+
+/// Numeric score for a detected mood label
+pub fn mood_value(mood: &str) -> Option<f64> {
+    match mood {
+        "positive" => Some(3.0),
+        "neutral" => Some(2.0),
+        "challenging" => Some(1.0),
+        _ => None,
+    }
+}
+
+/// Numeric score for a detected energy label
+pub fn energy_value(energy: &str) -> Option<f64> {
+    match energy {
+        "high" => Some(3.0),
+        "medium" => Some(2.0),
+        "low" => Some(1.0),
+        _ => None,
+    }
+}
+
+/// The words the user wrote most often across `entries`, ignoring short and common words
+pub fn top_themes(entries: &[Doc], count: usize) -> Vec<String> {
+    themes(
+        entries.iter().flat_map(|entry| user_lines(&entry.body)),
+        count,
+    )
+}
+
+/// The words used more than once across `lines`, most frequent first, ignoring short and
+/// common words
+pub fn themes<'a>(lines: impl IntoIterator<Item = &'a str>, count: usize) -> Vec<String> {
+    let mut word_counts: HashMap<String, usize> = HashMap::new();
+    for line in lines {
+        for word in line.split(|c: char| !c.is_alphanumeric() && c != '\'') {
+            let word = word.to_lowercase();
+            if word.chars().count() >= 4 && !STOP_WORDS.contains(&word.as_str()) {
+                *word_counts.entry(word).or_default() += 1;
+            }
+        }
+    }
+
+    let mut themes: Vec<_> = word_counts.into_iter().filter(|(_, n)| *n > 1).collect();
+    themes.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    themes
+        .into_iter()
+        .take(count)
+        .map(|(word, _)| word)
+        .collect()
+}
+
+/// Analysis versions recorded in an entry's frontmatter
+pub fn analysis_versions(doc: &Doc) -> Vec<AnalysisVersion> {
+    doc.frontmatter_extra
+        .get("analysis_versions")
+        .cloned()
+        .and_then(|versions| serde_json::from_value(versions).ok())
+        .unwrap_or_default()
+}
+
+/// Split an entry body into its current analysis text and the existing previous-analyses
+/// section (without its heading), if any
+pub fn split_analysis_sections<'a>(body: &'a str, heading: &str) -> (&'a str, Option<&'a str>) {
+    let marker = format!("\n## {heading}\n\n");
+    let Some(start) = body.find(&marker) else {
+        return ("", None);
+    };
+    let rest = &body[start + marker.len()..];
+
+    match rest.find(&format!("\n\n{PREVIOUS_ANALYSES_HEADING}\n\n")) {
+        Some(end) => (
+            rest[..end].trim(),
+            Some(rest[end + PREVIOUS_ANALYSES_HEADING.len() + 4..].trim()),
+        ),
+        None => (rest.trim(), None),
+    }
+}
+
+/// Section keeping superseded analyses, newest first, each labelled with its provenance
+pub fn previous_analyses_section(
+    superseded: &str,
+    superseded_version: Option<&AnalysisVersion>,
+    older: Option<&str>,
+) -> String {
+    let label = match superseded_version {
+        Some(version) => format!(
+            "### Version {} ({}, {}, template {})",
+            version.version,
+            version.generated_at.format("%Y-%m-%d %H:%M"),
+            version.backend,
+            version.template_hash
+        ),
+        None => "### Original analysis".to_string(),
+    };
+
+    let mut section = format!("{PREVIOUS_ANALYSES_HEADING}\n\n{label}\n\n{superseded}");
+    if let Some(older) = older.filter(|older| !older.is_empty()) {
+        section.push_str("\n\n");
+        section.push_str(older);
+    }
+    section
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{JournalSession, Speaker};
+    use aethel_core::{apply_patch, Patch, PatchMode};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn create_doc(vault_path: &Path, doc_type: &str, body: &str) -> Uuid {
+        apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(doc_type.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(json!({ "title": "Evening Journal Entry" })),
+                body: Some(body.to_string()),
+            },
+        )
+        .unwrap()
+        .uuid
+    }
+
+    #[test]
+    fn test_find_entries_filters_by_type_and_query() {
+        let temp_dir = TempDir::new().unwrap();
+        let first = create_doc(temp_dir.path(), ENTRY_DOC_TYPE, "Walked by the river");
+        create_doc(temp_dir.path(), ENTRY_DOC_TYPE, "Stayed in all day");
+        create_doc(temp_dir.path(), "journal.session", "[] river");
+
+        let all = find_entries(temp_dir.path(), &EntryFilter::default()).unwrap();
+        assert_eq!(all.len(), 2);
+
+        let river = find_entries(
+            temp_dir.path(),
+            &EntryFilter {
+                query: Some("RIVER".to_string()),
+                ..EntryFilter::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(river.len(), 1);
+        assert_eq!(river[0].uuid, first);
+
+        let future = find_entries(
+            temp_dir.path(),
+            &EntryFilter {
+                since: Some(NaiveDate::from_ymd_opt(2999, 1, 1).unwrap()),
+                ..EntryFilter::default()
+            },
+        )
+        .unwrap();
+        assert!(future.is_empty());
+    }
+
+    #[test]
+    fn test_user_lines_include_every_line_of_a_turn() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::System, "Starting evening session".to_string());
+        session.add_entry(Speaker::Coach, "How was today?".to_string());
+        session.add_entry(
+            Speaker::User,
+            "Long day.\n\nI talked to Sam about the move.\nIt went well.".to_string(),
+        );
+        session.add_entry(Speaker::Coach, "What made it go well?".to_string());
+        session.add_entry(Speaker::User, "We both listened.".to_string());
+        let body = format!(
+            "# Evening Journal Entry\n\n## Session Transcript\n\n{}\n\n## AI Analysis\n\nCalm.",
+            session.get_conversation_summary()
+        );
+
+        let lines: Vec<&str> = user_lines(&body).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "Long day.",
+                "I talked to Sam about the move.",
+                "It went well.",
+                "We both listened.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_analysis_sections() {
+        let body = "# Entry\n\n## Session Transcript\n\nhi\n\n## AI Analysis\n\nNew take\n\n## Previous Analyses\n\n### Version 1\n\nOld take";
+        let (current, previous) = split_analysis_sections(body, "AI Analysis");
+        assert_eq!(current, "New take");
+        assert_eq!(previous, Some("### Version 1\n\nOld take"));
+
+        let (current, previous) =
+            split_analysis_sections("# Entry\n\n## AI Analysis\n\nOnly", "AI Analysis");
+        assert_eq!(current, "Only");
+        assert_eq!(previous, None);
+    }
+
+    #[test]
+    fn test_previous_analyses_section_orders_newest_first() {
+        let version = AnalysisVersion {
+            version: 2,
+            generated_at: Utc::now(),
+            backend: "claude".to_string(),
+            template_hash: "0123456789abcdef".to_string(),
+        };
+        let section = previous_analyses_section(
+            "Second take",
+            Some(&version),
+            Some("### Version 1\n\nFirst take"),
+        );
+
+        assert!(section.starts_with("## Previous Analyses\n\n### Version 2 ("));
+        assert!(section.contains("claude, template 0123456789abcdef"));
+        assert!(section.find("Second take").unwrap() < section.find("First take").unwrap());
+    }
+}
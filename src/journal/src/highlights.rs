@@ -0,0 +1,291 @@
+use crate::entries::{self, EntryFilter};
+use crate::pack;
+use crate::sessions;
+use crate::state::{JournalSession, SessionMode, Speaker, TranscriptEntry};
+use aethel_core::{Doc, Patch, PatchMode};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::path::Path;
+use uuid::Uuid;
+
+/// A starred transcript line, as stored in its entry's `highlights`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Highlight {
+    /// Id of the transcript line, which stays the same when the line is revised
+    pub line_id: Uuid,
+    pub speaker: Speaker,
+    pub quote: String,
+    pub said_at: DateTime<Utc>,
+}
+
+impl Highlight {
+    fn from_line(line: &TranscriptEntry) -> Self {
+        Self {
+            line_id: line.id,
+            speaker: line.speaker,
+            quote: line.content.clone(),
+            said_at: line.timestamp,
+        }
+    }
+}
+
+/// A highlight and the entry it was starred in
+#[derive(Debug, Clone, PartialEq)]
+pub struct Quote {
+    pub entry_id: Uuid,
+    pub date: NaiveDate,
+    pub mode: Option<SessionMode>,
+    pub tags: Vec<String>,
+    pub highlight: Highlight,
+}
+
+/// The lines of a session that were starred with `/star`
+pub fn from_session(session: &JournalSession) -> Vec<Highlight> {
+    session
+        .transcript
+        .iter()
+        .filter(|line| line.starred && starrable(line))
+        .map(Highlight::from_line)
+        .collect()
+}
+
+/// The highlights stored in an entry, in the order they were said
+pub fn entry_highlights(entry: &Doc) -> Vec<Highlight> {
+    entry
+        .frontmatter_extra
+        .get("highlights")
+        .cloned()
+        .and_then(|highlights| serde_json::from_value(highlights).ok())
+        .unwrap_or_default()
+}
+
+/// The lines of an entry's session that can be starred, in the order `star` numbers them
+pub fn transcript_lines(vault_path: &Path, entry: &Doc) -> Result<Vec<TranscriptEntry>> {
+    let session = linked_session(vault_path, entry)?;
+    Ok(session.transcript.into_iter().filter(starrable).collect())
+}
+
+/// Star line `number` (counting from 1) of an entry's transcript. The line is added to the
+/// entry's highlights and marked in the session. Returns `None` if it was already starred.
+pub fn star(vault_path: &Path, entry_id: Uuid, number: usize) -> Result<Option<Highlight>> {
+    let entry = entries::read_entry(vault_path, entry_id)?;
+    let mut session = linked_session(vault_path, &entry)?;
+    let Some(line) = session
+        .transcript
+        .iter_mut()
+        .filter(|line| starrable(line))
+        .nth(number.saturating_sub(1))
+        .filter(|_| number > 0)
+    else {
+        anyhow::bail!(
+            "No line {number} in the transcript; run `journal star {entry_id}` to list them"
+        );
+    };
+
+    let mut highlights = entry_highlights(&entry);
+    if highlights
+        .iter()
+        .any(|highlight| highlight.line_id == line.id)
+    {
+        return Ok(None);
+    }
+    line.starred = true;
+    let highlight = Highlight::from_line(line);
+    highlights.push(highlight.clone());
+    highlights.sort_by_key(|highlight| highlight.said_at);
+
+    pack::apply_checked(
+        vault_path,
+        Patch {
+            uuid: Some(entry_id),
+            doc_type: None,
+            mode: PatchMode::MergeFrontmatter,
+            frontmatter: Some(json!({ "highlights": highlights })),
+            body: None,
+        },
+    )
+    .with_context(|| format!("Failed to update the highlights of {entry_id}"))?;
+    pack::apply_checked(
+        vault_path,
+        Patch {
+            uuid: session.metadata.session_doc_id,
+            doc_type: None,
+            mode: PatchMode::ReplaceBody,
+            frontmatter: None,
+            body: Some(sessions::transcript_body(&session)?),
+        },
+    )
+    .context("Failed to mark the line in the session")?;
+
+    Ok(Some(highlight))
+}
+
+/// Every highlight in the entries the filter matches, oldest first
+pub fn collect(vault_path: &Path, filter: &EntryFilter) -> Result<Vec<Quote>> {
+    let mut quotes = Vec::new();
+    for entry in entries::find_entries(vault_path, filter)? {
+        let mode = entry
+            .frontmatter_extra
+            .get("mode")
+            .cloned()
+            .and_then(|mode| serde_json::from_value(mode).ok());
+        for highlight in entry_highlights(&entry) {
+            quotes.push(Quote {
+                entry_id: entry.uuid,
+                date: entries::entry_date(&entry),
+                mode,
+                tags: entry.tags.clone(),
+                highlight,
+            });
+        }
+    }
+    Ok(quotes)
+}
+
+/// Only what the user and the coach said can be starred, not system notes
+fn starrable(line: &TranscriptEntry) -> bool {
+    line.speaker != Speaker::System
+}
+
+/// The session an entry was written from
+fn linked_session(vault_path: &Path, entry: &Doc) -> Result<JournalSession> {
+    let session_id = entry
+        .frontmatter_extra
+        .get("session_id")
+        .and_then(|id| id.as_str())
+        .and_then(|id| Uuid::parse_str(id).ok())
+        .context("Entry is not linked to a journal session")?;
+    sessions::load_session(vault_path, session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entries::ENTRY_DOC_TYPE;
+    use crate::sessions::SESSION_DOC_TYPE;
+    use aethel_core::{apply_patch, read_doc};
+    use tempfile::TempDir;
+
+    /// Save a session and an entry written from it, with the coach's line starred
+    fn create_entry(vault_path: &Path, tags: &[&str]) -> (Uuid, JournalSession) {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::System, "Starting evening session".to_string());
+        session.add_entry(Speaker::Coach, "What stood out today?".to_string());
+        session.add_entry(
+            Speaker::User,
+            "I finally said no to the extra project.".to_string(),
+        );
+        session.add_entry(Speaker::Coach, "Boundaries are a form of care.".to_string());
+        session.star_latest(&Speaker::Coach);
+
+        let session_id = apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(SESSION_DOC_TYPE.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(json!({ "mode": "Evening" })),
+                body: Some(sessions::transcript_body(&session).unwrap()),
+            },
+        )
+        .unwrap()
+        .uuid;
+        session.metadata.session_doc_id = Some(session_id);
+
+        let entry_id = apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(ENTRY_DOC_TYPE.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(json!({
+                    "session_id": session_id,
+                    "mode": "Evening",
+                    "session_type": "evening",
+                    "title": "Evening Journal Entry",
+                    "tags": tags,
+                    "highlights": from_session(&session),
+                })),
+                body: Some("# Entry".to_string()),
+            },
+        )
+        .unwrap()
+        .uuid;
+        (entry_id, session)
+    }
+
+    #[test]
+    fn test_star_adds_highlight_and_marks_session() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+        let (entry_id, session) = create_entry(vault_path, &[]);
+
+        let entry = read_doc(vault_path, &entry_id).unwrap();
+        assert_eq!(entry_highlights(&entry).len(), 1);
+        let lines = transcript_lines(vault_path, &entry).unwrap();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1].content, "I finally said no to the extra project.");
+
+        let highlight = star(vault_path, entry_id, 2).unwrap().unwrap();
+        assert_eq!(highlight.speaker, Speaker::User);
+        assert_eq!(star(vault_path, entry_id, 2).unwrap(), None);
+        assert_eq!(star(vault_path, entry_id, 3).unwrap(), None);
+        assert!(star(vault_path, entry_id, 0).is_err());
+        assert!(star(vault_path, entry_id, 4).is_err());
+
+        // Highlights stay in the order the lines were said
+        let entry = read_doc(vault_path, &entry_id).unwrap();
+        let quotes: Vec<String> = entry_highlights(&entry)
+            .into_iter()
+            .map(|highlight| highlight.quote)
+            .collect();
+        assert_eq!(
+            quotes,
+            vec![
+                "I finally said no to the extra project.",
+                "Boundaries are a form of care."
+            ]
+        );
+
+        let saved =
+            sessions::load_session(vault_path, session.metadata.session_doc_id.unwrap()).unwrap();
+        assert!(saved.transcript[2].starred);
+    }
+
+    #[test]
+    fn test_collect_filters_by_tag_and_mode() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+        let (work_entry, _) = create_entry(vault_path, &["work"]);
+        create_entry(vault_path, &["family"]);
+
+        assert_eq!(
+            collect(vault_path, &EntryFilter::default()).unwrap().len(),
+            2
+        );
+
+        let work = collect(
+            vault_path,
+            &EntryFilter {
+                tag: Some("work".to_string()),
+                ..EntryFilter::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(work.len(), 1);
+        assert_eq!(work[0].entry_id, work_entry);
+        assert_eq!(work[0].mode, Some(SessionMode::Evening));
+
+        let morning = collect(
+            vault_path,
+            &EntryFilter {
+                mode: Some(SessionMode::Morning),
+                ..EntryFilter::default()
+            },
+        )
+        .unwrap();
+        assert!(morning.is_empty());
+    }
+}
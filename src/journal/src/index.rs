@@ -0,0 +1,258 @@
+use crate::sessions;
+use crate::state::SessionStatus;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Location of the journal index, relative to the vault root
+pub const INDEX_FILE: &str = ".aethel/indexes/journal.index.json";
+
+/// Tracks the session in progress so it can be resumed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JournalIndex {
+    pub active_session: Option<Uuid>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl JournalIndex {
+    fn new(active_session: Option<Uuid>) -> Self {
+        Self {
+            active_session,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// How an index that couldn't be read was recovered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Recovery {
+    /// Restored from the backup of the last good index
+    Backup,
+    /// Rebuilt from the session docs in the vault
+    Rebuilt,
+}
+
+/// Reads and writes the journal index, recovering it when it's missing or damaged
+pub struct IndexStore {
+    vault_path: PathBuf,
+    path: PathBuf,
+}
+
+impl IndexStore {
+    pub fn new(vault_path: &Path) -> Self {
+        Self {
+            vault_path: vault_path.to_path_buf(),
+            path: vault_path.join(INDEX_FILE),
+        }
+    }
+
+    fn backup_path(&self) -> PathBuf {
+        self.path.with_extension("json.bak")
+    }
+
+    /// Load the index, falling back to its backup and then to a rebuild from the vault's
+    /// session docs. Reports how the index was recovered, if it needed to be.
+    pub fn load(&self) -> Result<(JournalIndex, Option<Recovery>)> {
+        let exists = self.path.exists();
+        if exists {
+            if let Ok(index) = self.read(&self.path) {
+                return Ok((index, None));
+            }
+        }
+
+        if let Ok(index) = self.read(&self.backup_path()) {
+            self.write(&index)?;
+            return Ok((index, Some(Recovery::Backup)));
+        }
+
+        // A missing index on a vault with nothing to resume is the normal starting point
+        let index = self.rebuild()?;
+        let recovered = exists || index.active_session.is_some();
+        Ok((index, recovered.then_some(Recovery::Rebuilt)))
+    }
+
+    pub fn set_active(&self, session_id: Uuid) -> Result<()> {
+        self.write(&JournalIndex::new(Some(session_id)))
+    }
+
+    pub fn clear(&self) -> Result<()> {
+        self.write(&JournalIndex::new(None))
+    }
+
+    /// Recreate the index from the most recently updated active session doc
+    pub fn rebuild(&self) -> Result<JournalIndex> {
+        let active_session = sessions::list_sessions(&self.vault_path)?
+            .into_iter()
+            .find(|session| {
+                session.metadata.status == SessionStatus::Active && !session.metadata.has_ended()
+            })
+            .and_then(|session| session.metadata.session_doc_id);
+
+        let index = JournalIndex::new(active_session);
+        self.write(&index)?;
+        Ok(index)
+    }
+
+    /// Read an index file, checking that its active session hasn't ended
+    fn read(&self, path: &Path) -> Result<JournalIndex> {
+        let content = fs::read_to_string(path).context("Failed to read index file")?;
+        let index: JournalIndex =
+            serde_json::from_str(&content).context("Failed to parse index file")?;
+
+        if let Some(session_id) = index.active_session {
+            let session = sessions::load_session(&self.vault_path, session_id)
+                .context("Index points at a session that can't be loaded")?;
+            if session.metadata.has_ended() {
+                anyhow::bail!("Index points at a session that has ended");
+            }
+        }
+        Ok(index)
+    }
+
+    /// Write the index atomically, keeping the previous good index as a backup
+    fn write(&self, index: &JournalIndex) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create indexes directory")?;
+        }
+        if self.read(&self.path).is_ok() {
+            fs::copy(&self.path, self.backup_path()).context("Failed to back up index file")?;
+        }
+
+        write_atomic(&self.path, &serde_json::to_string_pretty(index)?)
+            .context("Failed to write index file")
+    }
+}
+
+/// Replace `path` with `contents` so readers see either the old file or the new one, never a
+/// partial write
+pub fn write_atomic(path: &Path, contents: &str) -> Result<()> {
+    let mut temp_name = path.file_name().unwrap_or_default().to_os_string();
+    temp_name.push(".tmp");
+    let temp_path = path.with_file_name(temp_name);
+
+    let mut file = File::create(&temp_path).context("Failed to create temp file")?;
+    file.write_all(contents.as_bytes())
+        .context("Failed to write temp file")?;
+    file.sync_all().context("Failed to sync temp file")?;
+    fs::rename(&temp_path, path).context("Failed to move temp file into place")?;
+
+    // Persist the rename itself; not every platform can sync a directory
+    if let Some(dir) = path.parent().and_then(|parent| File::open(parent).ok()) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{JournalSession, SessionMode};
+    use aethel_core::{apply_patch, Patch, PatchMode};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    fn save(vault_path: &Path, session: &JournalSession) -> Uuid {
+        apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(sessions::SESSION_DOC_TYPE.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(json!({
+                    "mode": session.mode,
+                    "metadata": session.metadata,
+                    "session_type": "evening",
+                })),
+                body: Some(serde_json::to_string(&session.transcript).unwrap()),
+            },
+        )
+        .unwrap()
+        .uuid
+    }
+
+    #[test]
+    fn test_index_recovers_from_backup_then_rebuilds() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+        let store = IndexStore::new(vault_path);
+
+        // A fresh vault has nothing to recover
+        assert_eq!(store.load().unwrap().0.active_session, None);
+        assert_eq!(store.load().unwrap().1, None);
+
+        let session_id = save(vault_path, &JournalSession::new(SessionMode::Evening));
+        store.set_active(session_id).unwrap();
+        store.set_active(session_id).unwrap();
+        assert!(store.backup_path().exists());
+        assert!(!vault_path.join(format!("{INDEX_FILE}.tmp")).exists());
+
+        fs::write(vault_path.join(INDEX_FILE), "{ not json").unwrap();
+        let (index, recovery) = store.load().unwrap();
+        assert_eq!(index.active_session, Some(session_id));
+        assert_eq!(recovery, Some(Recovery::Backup));
+
+        fs::write(vault_path.join(INDEX_FILE), "").unwrap();
+        fs::remove_file(store.backup_path()).unwrap();
+        let (index, recovery) = store.load().unwrap();
+        assert_eq!(index.active_session, Some(session_id));
+        assert_eq!(recovery, Some(Recovery::Rebuilt));
+    }
+
+    #[test]
+    fn test_index_rejects_ended_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+        let store = IndexStore::new(vault_path);
+
+        let mut session = JournalSession::new(SessionMode::Morning);
+        session.archive(Uuid::new_v4());
+        let session_id = save(vault_path, &session);
+        fs::create_dir_all(vault_path.join(".aethel/indexes")).unwrap();
+        fs::write(
+            vault_path.join(INDEX_FILE),
+            serde_json::to_string(&JournalIndex::new(Some(session_id))).unwrap(),
+        )
+        .unwrap();
+
+        let (index, recovery) = store.load().unwrap();
+        assert_eq!(index.active_session, None);
+        assert_eq!(recovery, Some(Recovery::Rebuilt));
+    }
+
+    #[test]
+    fn test_rebuild_skips_completed_sessions_without_status() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+        let store = IndexStore::new(vault_path);
+
+        // A completed session saved before sessions recorded a `status`
+        apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(sessions::SESSION_DOC_TYPE.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(json!({
+                    "mode": "Evening",
+                    "session_type": "evening",
+                    "metadata": {
+                        "session_doc_id": null,
+                        "final_entry_id": null,
+                        "completed_at": "2024-03-14T21:00:00Z",
+                        "custom_fields": {},
+                    },
+                })),
+                body: Some("[]".to_string()),
+            },
+        )
+        .unwrap();
+
+        let (index, recovery) = store.load().unwrap();
+        assert_eq!(index.active_session, None);
+        assert_eq!(recovery, None);
+    }
+}
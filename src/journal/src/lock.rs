@@ -0,0 +1,223 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+/// Location of the vault lock file, relative to the vault root
+pub const LOCK_FILE: &str = ".aethel/journal.lock";
+
+/// Locks from other machines can't be checked for a live process, so they're only treated as
+/// stale after this long
+const FOREIGN_LOCK_MAX_AGE_HOURS: i64 = 24;
+
+/// Who holds the vault lock
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    pub host: String,
+    pub started_at: DateTime<Utc>,
+}
+
+impl LockOwner {
+    fn current() -> Self {
+        Self {
+            pid: std::process::id(),
+            host: hostname(),
+            started_at: Utc::now(),
+        }
+    }
+
+    /// Whether the process that took the lock has gone away without releasing it
+    fn is_stale(&self, now: DateTime<Utc>) -> bool {
+        if self.host == hostname() {
+            !process_alive(self.pid)
+        } else {
+            now - self.started_at > Duration::hours(FOREIGN_LOCK_MAX_AGE_HOURS)
+        }
+    }
+}
+
+/// Advisory lock that keeps two journal processes from writing to one vault at once. The lock
+/// is released when this is dropped.
+#[derive(Debug)]
+pub struct VaultLock {
+    path: PathBuf,
+}
+
+impl VaultLock {
+    /// Take the lock, replacing it if the process holding it is gone
+    pub fn acquire(vault_path: &Path) -> Result<Self> {
+        let path = vault_path.join(LOCK_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create .aethel directory")?;
+        }
+
+        if try_create(&path)? {
+            return Ok(Self { path });
+        }
+
+        let content = fs::read_to_string(&path).ok();
+        match content.as_deref().and_then(parse_owner) {
+            Some(owner) if !owner.is_stale(Utc::now()) => anyhow::bail!(
+                "The vault is in use by another journal process (PID {} on {}, since {}). \
+                 Finish that session first, or delete {} if that process is gone.",
+                owner.pid,
+                owner.host,
+                owner
+                    .started_at
+                    .with_timezone(&chrono::Local)
+                    .format("%Y-%m-%d %H:%M"),
+                path.display()
+            ),
+            // Stale or unreadable - take it over
+            _ => {
+                take_over(&path, content.as_deref())?;
+                Ok(Self { path })
+            }
+        }
+    }
+}
+
+impl Drop for VaultLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Create the lock file for this process. Returns false if it already exists.
+fn try_create(path: &Path) -> Result<bool> {
+    let mut file = match OpenOptions::new().write(true).create_new(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => return Ok(false),
+        Err(e) => return Err(e).context("Failed to create vault lock"),
+    };
+    let owner = serde_json::to_string(&LockOwner::current())?;
+    file.write_all(owner.as_bytes())
+        .context("Failed to write vault lock")?;
+    Ok(true)
+}
+
+/// Replace the lock file, which held `stale` when it was found to be stale. Two processes can
+/// find the same stale lock, so only one may take it over at a time, and only while the lock
+/// still holds what it found.
+fn take_over(path: &Path, stale: Option<&str>) -> Result<()> {
+    let mut takeover_name = path.file_name().unwrap_or_default().to_os_string();
+    takeover_name.push(".takeover");
+    let takeover_path = path.with_file_name(takeover_name);
+    if !try_create(&takeover_path)? {
+        anyhow::bail!(
+            "Another journal process is taking over the vault lock. Try again, or delete {} if \
+             no other journal is running.",
+            takeover_path.display()
+        );
+    }
+
+    let result = replace_stale(path, stale);
+    let _ = fs::remove_file(&takeover_path);
+    result
+}
+
+fn replace_stale(path: &Path, stale: Option<&str>) -> Result<()> {
+    let raced = || anyhow::anyhow!("Another journal process took the vault lock at the same time");
+    if fs::read_to_string(path).ok().as_deref() != stale {
+        return Err(raced());
+    }
+    match fs::remove_file(path) {
+        Err(e) if e.kind() != ErrorKind::NotFound => {
+            return Err(e).context("Failed to remove stale vault lock")
+        }
+        _ => {}
+    }
+    match try_create(path)? {
+        true => Ok(()),
+        false => Err(raced()),
+    }
+}
+
+fn parse_owner(content: &str) -> Option<LockOwner> {
+    serde_json::from_str(content).ok()
+}
+
+fn hostname() -> String {
+    ["HOSTNAME", "COMPUTERNAME"]
+        .into_iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .chain(fs::read_to_string("/etc/hostname").ok())
+        .map(|name| name.trim().to_string())
+        .find(|name| !name.is_empty())
+        .unwrap_or_else(|| "unknown host".to_string())
+}
+
+/// Whether a process with this PID is running. When that can't be determined the process is
+/// assumed to be alive, so a lock is never taken from a running journal.
+fn process_alive(pid: u32) -> bool {
+    if cfg!(target_os = "linux") {
+        return Path::new("/proc").join(pid.to_string()).exists();
+    }
+    std::process::Command::new("kill")
+        .args(["-0", &pid.to_string()])
+        .stderr(std::process::Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_is_exclusive_until_dropped() {
+        let temp_dir = TempDir::new().unwrap();
+
+        let lock = VaultLock::acquire(temp_dir.path()).unwrap();
+        let error = VaultLock::acquire(temp_dir.path()).unwrap_err().to_string();
+        assert!(error.contains(&format!("PID {}", std::process::id())));
+
+        drop(lock);
+        assert!(!temp_dir.path().join(LOCK_FILE).exists());
+        VaultLock::acquire(temp_dir.path()).unwrap();
+    }
+
+    #[test]
+    fn test_stale_lock_is_replaced() {
+        let temp_dir = TempDir::new().unwrap();
+        let path = temp_dir.path().join(LOCK_FILE);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        // A process on this host that no longer exists
+        let stale = LockOwner {
+            pid: u32::MAX,
+            host: hostname(),
+            started_at: Utc::now(),
+        };
+        let stale = serde_json::to_string(&stale).unwrap();
+        fs::write(&path, &stale).unwrap();
+        let _lock = VaultLock::acquire(temp_dir.path()).unwrap();
+        let owner = fs::read_to_string(&path).unwrap();
+        assert_eq!(parse_owner(&owner).unwrap().pid, std::process::id());
+
+        // A second process that found the same stale lock must not remove the fresh one
+        let error = take_over(&path, Some(&stale)).unwrap_err().to_string();
+        assert!(error.contains("at the same time"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), owner);
+        assert!(!path.with_file_name("journal.lock.takeover").exists());
+
+        // Nor may two processes take a lock over at once
+        fs::write(path.with_file_name("journal.lock.takeover"), "").unwrap();
+        let error = take_over(&path, Some(&owner)).unwrap_err().to_string();
+        assert!(error.contains("is taking over"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), owner);
+
+        let foreign = LockOwner {
+            pid: 1,
+            host: "elsewhere".to_string(),
+            started_at: Utc::now() - Duration::hours(2),
+        };
+        assert!(!foreign.is_stale(Utc::now()));
+        assert!(foreign.is_stale(Utc::now() + Duration::days(1)));
+    }
+}
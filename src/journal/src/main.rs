@@ -1,21 +1,56 @@
 mod action;
+mod audit;
+mod backend;
+mod commands;
+mod config;
+mod digest;
+mod doctor;
+mod editor;
 mod effects;
+mod entries;
+mod highlights;
+mod index;
+mod lock;
+mod mentions;
+mod pack;
+mod pending;
+mod prompt;
+mod redact;
+mod resurface;
+mod sessions;
 mod state;
+mod stats;
+mod streak;
+mod tags;
+mod tui;
 mod update;
+mod upgrade;
 mod view;
 
 use action::{Action, InputContext, UserInput};
 use anyhow::{Context, Result};
+use chrono::NaiveDate;
 use clap::{Arg, Command as ClapCommand};
+use config::{JournalConfig, ResurfacingConfig, StreakConfig};
 use effects::{Effect, EffectRunner};
-use state::State;
-use std::io;
-use std::path::PathBuf;
+use index::{IndexStore, Recovery};
+use lock::VaultLock;
+use state::{JournalSession, SessionMode, SessionStatus, State};
+use std::collections::VecDeque;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use streak::StreakStatus;
+use tui::{SaveState, Tui};
 use uuid::Uuid;
 
 #[derive(Debug)]
 struct AppConfig {
     vault_path: PathBuf,
+    local_only: bool,
+    /// Run sessions in the full-screen interface
+    tui: bool,
+    /// Refuse commands that would change the vault
+    read_only: bool,
     command: AppCommand,
 }
 
@@ -23,33 +58,120 @@ struct AppConfig {
 enum AppCommand {
     New,
     Resume(Option<Uuid>),
+    Audit(commands::AuditOptions),
+    Analyze(commands::AnalyzeOptions),
+    Reanalyze(commands::ReanalyzeOptions),
+    Digest(commands::DigestOptions),
+    Stats(commands::StatsOptions),
+    Streak,
+    Sessions(commands::SessionsOptions),
+    Doctor(commands::DoctorOptions),
+    Upgrade(commands::UpgradeOptions),
+    Tags(commands::TagsCommand),
+    People(commands::PeopleOptions),
+    Mentions(commands::MentionsOptions),
+    Star(commands::StarOptions),
+    Highlights(commands::HighlightsOptions),
+}
+
+impl AppCommand {
+    /// Whether the command can change the vault, and so needs the vault lock
+    fn writes(&self) -> bool {
+        match self {
+            AppCommand::Audit(options) => options.purge,
+            AppCommand::Analyze(options) => options.retry_pending,
+            AppCommand::Doctor(options) => options.fix,
+            AppCommand::Upgrade(options) => !options.dry_run,
+            AppCommand::Tags(command) => *command != commands::TagsCommand::List,
+            AppCommand::People(options) => options.reindex,
+            AppCommand::Star(options) => options.line.is_some(),
+            AppCommand::Stats(_)
+            | AppCommand::Streak
+            | AppCommand::Sessions(_)
+            | AppCommand::Mentions(_)
+            | AppCommand::Highlights(_) => false,
+            AppCommand::New
+            | AppCommand::Resume(_)
+            | AppCommand::Reanalyze(_)
+            | AppCommand::Digest(_) => true,
+        }
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
     let config = parse_args()?;
 
-    let mut app = JournalApp::new(config.vault_path).await?;
+    // Only one journal process may change a vault at a time; reports just read it
+    let _lock = match (config.command.writes(), config.read_only) {
+        (true, true) => {
+            anyhow::bail!("This command changes the vault and can't run with --read-only")
+        }
+        (true, false) => Some(VaultLock::acquire(&config.vault_path)?),
+        (false, _) => None,
+    };
 
+    let offer_resume = matches!(config.command, AppCommand::New);
     let initial_action = match config.command {
+        // Reporting commands run without starting a session
+        AppCommand::Audit(options) => return commands::run_audit(&config.vault_path, &options),
+        AppCommand::Analyze(options) => {
+            return commands::run_analyze(&config.vault_path, &options, config.local_only).await
+        }
+        AppCommand::Reanalyze(options) => {
+            return commands::run_reanalyze(&config.vault_path, &options, config.local_only).await
+        }
+        AppCommand::Stats(options) => return commands::run_stats(&config.vault_path, &options),
+        AppCommand::Streak => return commands::run_streak(&config.vault_path),
+        AppCommand::Sessions(options) => {
+            return commands::run_sessions(&config.vault_path, &options)
+        }
+        AppCommand::Doctor(options) => {
+            return commands::run_doctor(&config.vault_path, &options).await
+        }
+        AppCommand::Upgrade(options) => return commands::run_upgrade(&config.vault_path, &options),
+        AppCommand::Tags(command) => return commands::run_tags(&config.vault_path, &command),
+        AppCommand::People(options) => return commands::run_people(&config.vault_path, &options),
+        AppCommand::Mentions(options) => {
+            return commands::run_mentions(&config.vault_path, &options)
+        }
+        AppCommand::Star(options) => return commands::run_star(&config.vault_path, &options),
+        AppCommand::Highlights(options) => {
+            return commands::run_highlights(&config.vault_path, &options)
+        }
+        AppCommand::Digest(options) => {
+            return commands::run_digest(&config.vault_path, &options, config.local_only).await
+        }
         AppCommand::New => Action::Start,
         AppCommand::Resume(Some(session_id)) => Action::Resume(session_id),
-        AppCommand::Resume(None) => {
-            // Try to find the most recent active session
-            match app.find_active_session().await {
-                Ok(Some(session_id)) => Action::Resume(session_id),
-                Ok(None) => {
-                    // No active session found, start new one
-                    Action::Start
-                }
-                Err(_) => {
-                    // Error finding active session, start new one
-                    Action::Start
-                }
+        AppCommand::Resume(None) => match latest_resumable(&config.vault_path).await {
+            Some(session) => {
+                println!("Resuming your {}.", sessions::describe(&session));
+                session
+                    .metadata
+                    .session_doc_id
+                    .map_or(Action::Start, Action::Resume)
             }
-        }
+            None => {
+                println!("No paused session to resume; starting a new one.");
+                Action::Start
+            }
+        },
     };
 
+    let mut app = JournalApp::new(config.vault_path).await?;
+    if config.local_only {
+        app.set_local_only();
+    } else {
+        app.complete_pending_analyses().await;
+    }
+    let initial_action = match offer_resume {
+        true => app.offer_to_resume().await?,
+        false => initial_action,
+    };
+    if config.tui {
+        app.enable_tui()?;
+    }
     app.run(initial_action).await
 }
 
@@ -78,6 +200,27 @@ fn parse_args() -> Result<AppConfig> {
                 .help("Path to the journal vault (default: ~/Documents/vault)")
                 .value_parser(clap::value_parser!(PathBuf)),
         )
+        .arg(
+            Arg::new("private")
+                .long("private")
+                .global(true)
+                .help("Local-only mode: never send anything to an AI backend")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("read-only")
+                .long("read-only")
+                .global(true)
+                .help("Only run commands that read the vault, without taking the vault lock")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("tui")
+                .long("tui")
+                .global(true)
+                .help("Use the full-screen interface for the session")
+                .action(clap::ArgAction::SetTrue),
+        )
         .subcommand(ClapCommand::new("new").about("Start a new journal session"))
         .subcommand(
             ClapCommand::new("resume")
@@ -89,6 +232,269 @@ fn parse_args() -> Result<AppConfig> {
                         .value_parser(clap::value_parser!(Uuid)),
                 ),
         )
+        .subcommand(
+            ClapCommand::new("audit")
+                .about("Show everything sent to and received from the AI backend")
+                .arg(
+                    Arg::new("session")
+                        .long("session")
+                        .value_name("UUID")
+                        .help("Only show (or purge) exchanges for this session")
+                        .value_parser(clap::value_parser!(Uuid)),
+                )
+                .arg(
+                    Arg::new("full")
+                        .long("full")
+                        .help("Include the full prompt and response text")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("purge")
+                        .long("purge")
+                        .help("Delete recorded exchanges instead of showing them")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .long("yes")
+                        .short('y')
+                        .help("Don't ask for confirmation before purging")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("analyze")
+                .about("List entries whose AI analysis is still pending")
+                .arg(
+                    Arg::new("pending")
+                        .long("pending")
+                        .help("Retry the pending analyses and add them to their entries")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("reanalyze")
+                .about("Regenerate the analysis of existing entries with the current prompt")
+                .arg(
+                    Arg::new("entry-id")
+                        .value_name("UUID")
+                        .help("Entry to reanalyze")
+                        .value_parser(clap::value_parser!(Uuid))
+                        .conflicts_with_all(["since", "query"]),
+                )
+                .arg(
+                    Arg::new("since")
+                        .long("since")
+                        .value_name("YYYY-MM-DD")
+                        .help("Reanalyze entries created on or after this date")
+                        .value_parser(parse_date),
+                )
+                .arg(
+                    Arg::new("query")
+                        .long("query")
+                        .value_name("TEXT")
+                        .help("Reanalyze entries containing this text"),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .long("yes")
+                        .short('y')
+                        .help("Don't ask for confirmation before reanalyzing several entries")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("digest")
+                .about("Write a weekly, monthly or yearly review of your entries")
+                .arg(
+                    Arg::new("week")
+                        .long("week")
+                        .help("Review a week (Monday to Sunday)")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("month")
+                        .long("month")
+                        .help("Review a calendar month")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("year")
+                        .long("year")
+                        .help("Review a calendar year")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .group(
+                    clap::ArgGroup::new("period")
+                        .args(["week", "month", "year"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("date")
+                        .long("date")
+                        .value_name("YYYY-MM-DD")
+                        .help("Any day in the period to review (default: today)")
+                        .value_parser(parse_date),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("stats")
+                .about("Show mood, energy and journaling trends")
+                .arg(
+                    Arg::new("since")
+                        .long("since")
+                        .value_name("YYYY-MM-DD")
+                        .help("Only include entries from this date on")
+                        .value_parser(parse_date),
+                )
+                .arg(
+                    Arg::new("until")
+                        .long("until")
+                        .value_name("YYYY-MM-DD")
+                        .help("Only include entries up to this date")
+                        .value_parser(parse_date),
+                )
+                .arg(
+                    Arg::new("json")
+                        .long("json")
+                        .help("Print the statistics as JSON")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("streak").about("Show journaling streaks and weekly goal progress"),
+        )
+        .subcommand(
+            ClapCommand::new("sessions")
+                .about("List paused and unfinished sessions")
+                .arg(
+                    Arg::new("archived")
+                        .long("archived")
+                        .help("List archived sessions and the entries written from them")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("doctor")
+                .about("Check the vault for inconsistencies")
+                .arg(
+                    Arg::new("fix")
+                        .long("fix")
+                        .help("Repair the problems that are found")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("upgrade")
+                .about("Install the latest journal pack and migrate existing docs to it")
+                .arg(
+                    Arg::new("dry-run")
+                        .long("dry-run")
+                        .help("Show what would change without writing anything")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("people")
+                .about("List the people, places and projects your entries mention")
+                .arg(
+                    Arg::new("reindex")
+                        .long("reindex")
+                        .help("Find the mentions in every entry again, e.g. after changing aliases")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("mentions")
+                .about("Show every entry that mentions someone or something")
+                .arg(
+                    Arg::new("name")
+                        .value_name("NAME")
+                        .help("A name or one of its aliases")
+                        .required(true),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("star")
+                .about("Star a line of an entry's transcript, or list the lines to pick from")
+                .arg(entry_id_arg())
+                .arg(
+                    Arg::new("line")
+                        .value_name("LINE")
+                        .help("Number of the line to star, as listed without it")
+                        .value_parser(clap::value_parser!(usize)),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("highlights")
+                .about("Show the lines you've starred across your entries")
+                .arg(
+                    Arg::new("tag")
+                        .long("tag")
+                        .value_name("TAG")
+                        .help("Only highlights from entries with this tag")
+                        .value_parser(parse_tag),
+                )
+                .arg(
+                    Arg::new("mode")
+                        .long("mode")
+                        .value_name("morning|evening")
+                        .help("Only highlights from morning or evening sessions")
+                        .value_parser(parse_mode),
+                )
+                .arg(
+                    Arg::new("since")
+                        .long("since")
+                        .value_name("YYYY-MM-DD")
+                        .help("Only highlights from entries on or after this date")
+                        .value_parser(parse_date),
+                )
+                .arg(
+                    Arg::new("until")
+                        .long("until")
+                        .value_name("YYYY-MM-DD")
+                        .help("Only highlights from entries up to this date")
+                        .value_parser(parse_date),
+                ),
+        )
+        .subcommand(
+            ClapCommand::new("tags")
+                .about("List, add, rename and merge entry tags")
+                .subcommand(
+                    ClapCommand::new("list").about("List tags with how many entries use each"),
+                )
+                .subcommand(
+                    ClapCommand::new("add")
+                        .about("Add tags to an entry")
+                        .arg(entry_id_arg())
+                        .arg(tags_arg("Tags to add")),
+                )
+                .subcommand(
+                    ClapCommand::new("remove")
+                        .about("Remove tags from an entry")
+                        .arg(entry_id_arg())
+                        .arg(tags_arg("Tags to remove")),
+                )
+                .subcommand(
+                    ClapCommand::new("rename")
+                        .about("Rename a tag on every entry")
+                        .arg(Arg::new("from").required(true).value_parser(parse_tag))
+                        .arg(Arg::new("to").required(true).value_parser(parse_tag)),
+                )
+                .subcommand(
+                    ClapCommand::new("merge")
+                        .about("Replace several tags with one on every entry")
+                        .arg(tags_arg("Tags to merge away"))
+                        .arg(
+                            Arg::new("into")
+                                .long("into")
+                                .value_name("TAG")
+                                .help("The tag that replaces them")
+                                .required(true)
+                                .value_parser(parse_tag),
+                        ),
+                ),
+        )
         .get_matches();
 
     let vault_path = matches
@@ -102,79 +508,484 @@ fn parse_args() -> Result<AppConfig> {
             let session_id = sub_matches.get_one::<Uuid>("session-id").cloned();
             AppCommand::Resume(session_id)
         }
+        Some(("audit", sub_matches)) => AppCommand::Audit(commands::AuditOptions {
+            session_id: sub_matches.get_one::<Uuid>("session").cloned(),
+            show_content: sub_matches.get_flag("full"),
+            purge: sub_matches.get_flag("purge"),
+            assume_yes: sub_matches.get_flag("yes"),
+        }),
+        Some(("analyze", sub_matches)) => AppCommand::Analyze(commands::AnalyzeOptions {
+            retry_pending: sub_matches.get_flag("pending"),
+        }),
+        Some(("reanalyze", sub_matches)) => AppCommand::Reanalyze(commands::ReanalyzeOptions {
+            entry_id: sub_matches.get_one::<Uuid>("entry-id").cloned(),
+            filter: entries::EntryFilter {
+                since: sub_matches.get_one::<NaiveDate>("since").cloned(),
+                query: sub_matches.get_one::<String>("query").cloned(),
+                ..entries::EntryFilter::default()
+            },
+            assume_yes: sub_matches.get_flag("yes"),
+        }),
+        Some(("stats", sub_matches)) => AppCommand::Stats(commands::StatsOptions {
+            since: sub_matches.get_one::<NaiveDate>("since").cloned(),
+            until: sub_matches.get_one::<NaiveDate>("until").cloned(),
+            json: sub_matches.get_flag("json"),
+        }),
+        Some(("streak", _)) => AppCommand::Streak,
+        Some(("sessions", sub_matches)) => AppCommand::Sessions(commands::SessionsOptions {
+            archived: sub_matches.get_flag("archived"),
+        }),
+        Some(("doctor", sub_matches)) => AppCommand::Doctor(commands::DoctorOptions {
+            fix: sub_matches.get_flag("fix"),
+        }),
+        Some(("upgrade", sub_matches)) => AppCommand::Upgrade(commands::UpgradeOptions {
+            dry_run: sub_matches.get_flag("dry-run"),
+        }),
+        Some(("people", sub_matches)) => AppCommand::People(commands::PeopleOptions {
+            reindex: sub_matches.get_flag("reindex"),
+        }),
+        Some(("mentions", sub_matches)) => AppCommand::Mentions(commands::MentionsOptions {
+            name: sub_matches
+                .get_one::<String>("name")
+                .cloned()
+                .unwrap_or_default(),
+        }),
+        Some(("star", sub_matches)) => AppCommand::Star(commands::StarOptions {
+            entry_id: *sub_matches
+                .get_one::<Uuid>("entry-id")
+                .expect("entry-id is required"),
+            line: sub_matches.get_one::<usize>("line").cloned(),
+        }),
+        Some(("highlights", sub_matches)) => AppCommand::Highlights(commands::HighlightsOptions {
+            filter: entries::EntryFilter {
+                since: sub_matches.get_one::<NaiveDate>("since").cloned(),
+                until: sub_matches.get_one::<NaiveDate>("until").cloned(),
+                tag: sub_matches.get_one::<String>("tag").cloned(),
+                mode: sub_matches.get_one::<SessionMode>("mode").cloned(),
+                ..entries::EntryFilter::default()
+            },
+        }),
+        Some(("tags", sub_matches)) => {
+            let entry_id = |matches: &clap::ArgMatches| {
+                *matches
+                    .get_one::<Uuid>("entry-id")
+                    .expect("entry-id is required")
+            };
+            let tags = |matches: &clap::ArgMatches| {
+                matches
+                    .get_many::<String>("tags")
+                    .into_iter()
+                    .flatten()
+                    .cloned()
+                    .collect()
+            };
+            let tag = |matches: &clap::ArgMatches, name: &str| {
+                matches.get_one::<String>(name).cloned().unwrap_or_default()
+            };
+            AppCommand::Tags(match sub_matches.subcommand() {
+                Some(("add", matches)) => commands::TagsCommand::Add {
+                    entry_id: entry_id(matches),
+                    tags: tags(matches),
+                },
+                Some(("remove", matches)) => commands::TagsCommand::Remove {
+                    entry_id: entry_id(matches),
+                    tags: tags(matches),
+                },
+                Some(("rename", matches)) => commands::TagsCommand::Rename {
+                    from: tag(matches, "from"),
+                    to: tag(matches, "to"),
+                },
+                Some(("merge", matches)) => commands::TagsCommand::Merge {
+                    tags: tags(matches),
+                    into: tag(matches, "into"),
+                },
+                _ => commands::TagsCommand::List,
+            })
+        }
+        Some(("digest", sub_matches)) => AppCommand::Digest(commands::DigestOptions {
+            period: if sub_matches.get_flag("year") {
+                digest::PeriodKind::Year
+            } else if sub_matches.get_flag("month") {
+                digest::PeriodKind::Month
+            } else {
+                digest::PeriodKind::Week
+            },
+            date: sub_matches
+                .get_one::<NaiveDate>("date")
+                .cloned()
+                .unwrap_or_else(|| chrono::Local::now().date_naive()),
+        }),
         _ => AppCommand::New, // Default to new session
     };
 
     Ok(AppConfig {
         vault_path,
+        local_only: matches.get_flag("private"),
+        tui: matches.get_flag("tui"),
+        read_only: matches.get_flag("read-only"),
         command,
     })
 }
 
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("'{value}' is not a date in YYYY-MM-DD format"))
+}
+
+fn parse_mode(value: &str) -> Result<SessionMode, String> {
+    match value.to_lowercase().as_str() {
+        "morning" => Ok(SessionMode::Morning),
+        "evening" => Ok(SessionMode::Evening),
+        _ => Err(format!("'{value}' is not a mode; use morning or evening")),
+    }
+}
+
+fn parse_tag(value: &str) -> Result<String, String> {
+    tags::normalize(value).ok_or_else(|| "a tag can't be empty".to_string())
+}
+
+fn entry_id_arg() -> Arg {
+    Arg::new("entry-id")
+        .value_name("ENTRY_UUID")
+        .required(true)
+        .value_parser(clap::value_parser!(Uuid))
+}
+
+fn tags_arg(help: &'static str) -> Arg {
+    Arg::new("tags")
+        .value_name("TAG")
+        .help(help)
+        .required(true)
+        .num_args(1..)
+        .value_parser(parse_tag)
+}
+
+async fn find_active_session(vault_path: &Path) -> Result<Option<Uuid>> {
+    let (index, recovery) = IndexStore::new(vault_path).load()?;
+    match recovery {
+        Some(Recovery::Backup) => {
+            eprintln!("⚠️  The journal index was damaged and has been restored from its backup.")
+        }
+        Some(Recovery::Rebuilt) => {
+            eprintln!("⚠️  The journal index was missing or damaged and has been rebuilt.")
+        }
+        None => {}
+    }
+    Ok(index.active_session)
+}
+
+/// The most recently updated session that can be resumed, if any
+async fn latest_resumable(vault_path: &Path) -> Option<JournalSession> {
+    let active = find_active_session(vault_path).await.ok().flatten();
+    sessions::find_resumable(vault_path, active)
+        .ok()?
+        .into_iter()
+        .next()
+}
+
 struct JournalApp {
     state: State,
     effect_runner: EffectRunner,
+    private_modes: Vec<SessionMode>,
+    resurfacing: ResurfacingConfig,
+    streaks: StreakConfig,
+    tui: Option<Tui>,
 }
 
 impl JournalApp {
     async fn new(vault_path: PathBuf) -> Result<Self> {
-        // Initialize vault if it doesn't exist
-        if !vault_path.join(".aethel").exists() {
-            let effect_runner = EffectRunner::new(vault_path.clone());
-            effect_runner
-                .run_effect(Effect::InitializeVault(vault_path.clone()))
-                .await?;
+        // Initialize vault if the journal pack isn't installed yet
+        match pack::installed_version(&vault_path) {
+            None => {
+                let effect_runner = EffectRunner::new(vault_path.clone());
+                effect_runner
+                    .run_effect(Effect::InitializeVault(vault_path.clone()))
+                    .await?;
+            }
+            Some(installed) if installed < pack::current_version() => eprintln!(
+                "A newer journal pack ({}) is available. Run `journal upgrade` to install it.",
+                pack::current_version()
+            ),
+            Some(_) => {}
         }
 
+        let config = JournalConfig::load(&vault_path)?;
+
         Ok(Self {
             state: State::Initializing,
-            effect_runner: EffectRunner::new(vault_path),
+            effect_runner: EffectRunner::with_config(vault_path, &config)?,
+            private_modes: config.private_modes,
+            resurfacing: config.resurfacing,
+            streaks: config.streaks,
+            tui: None,
         })
     }
 
-    async fn find_active_session(&self) -> Result<Option<Uuid>> {
-        // Read the index file to find active session
-        let index_path = self
-            .effect_runner
-            .vault_path
-            .join(".aethel/indexes/journal.index.json");
+    /// Before starting a new session, offer to pick up a paused or interrupted one. Without a
+    /// terminal to ask on, a new session is started and the old one is left for later.
+    async fn offer_to_resume(&self) -> Result<Action> {
+        let Some(mut session) = latest_resumable(&self.effect_runner.vault_path).await else {
+            return Ok(Action::Start);
+        };
+        let Some(session_id) = session.metadata.session_doc_id else {
+            return Ok(Action::Start);
+        };
+        if !io::stdin().is_terminal() {
+            return Ok(Action::Start);
+        }
+
+        let choice = dialoguer::Select::new()
+            .with_prompt(format!("You have a {}", sessions::describe(&session)))
+            .items(&[
+                "Resume it",
+                "Start a new session (keep it for later)",
+                "Abandon it and start a new session",
+            ])
+            .default(0)
+            .interact()
+            .context("Failed to read your choice")?;
+        session.metadata.status = match choice {
+            0 => return Ok(Action::Resume(session_id)),
+            1 => SessionStatus::Paused,
+            _ => SessionStatus::Abandoned,
+        };
 
-        if !index_path.exists() {
-            return Ok(None);
+        // Saving points the index at the old session, so clear it for the new one
+        self.effect_runner
+            .run_effect(Effect::SaveSession(session))
+            .await?;
+        self.effect_runner.run_effect(Effect::ClearIndex).await?;
+        Ok(Action::Start)
+    }
+
+    fn enable_tui(&mut self) -> Result<()> {
+        self.tui = Some(Tui::enter(self.effect_runner.backend.name())?);
+        Ok(())
+    }
+
+    /// Never contact the AI backend for the rest of this run
+    fn set_local_only(&mut self) {
+        self.effect_runner.local_only = true;
+    }
+
+    /// Fill in analyses that were deferred while the AI backend was unavailable. Failures are
+    /// not fatal; the analyses stay queued for the next attempt.
+    async fn complete_pending_analyses(&self) {
+        if let Ok(report) = self.effect_runner.complete_pending_analyses().await {
+            if report.completed > 0 {
+                println!(
+                    "\n✨ Added {} pending AI analyses to earlier journal entries.",
+                    report.completed
+                );
+            }
         }
+    }
 
-        let content = tokio::fs::read_to_string(&index_path)
-            .await
-            .context("Failed to read index file")?;
+    /// Show "on this day" snippets from past entries and, if configured, share them with the
+    /// coach. Problems reading old entries never prevent a session from starting.
+    async fn resurface_past_entries(&mut self, private_mode: bool) -> Result<()> {
+        if !self.resurfacing.enabled {
+            return Ok(());
+        }
 
-        let index: serde_json::Value =
-            serde_json::from_str(&content).context("Failed to parse index file")?;
+        let today = chrono::Local::now().date_naive();
+        let Ok(resurfaced) = resurface::load_resurfaced(&self.effect_runner.vault_path, today)
+        else {
+            return Ok(());
+        };
 
-        if let Some(session_id_str) = index.get("active_session").and_then(|v| v.as_str()) {
-            let session_id =
-                Uuid::parse_str(session_id_str).context("Invalid session ID in index")?;
-            Ok(Some(session_id))
-        } else {
-            Ok(None)
+        let (shown, shareable): (Vec<_>, Vec<_>) = resurfaced
+            .into_iter()
+            .map(|(entry, shareable)| (entry.clone(), shareable.then_some(entry)))
+            .unzip();
+        match self.tui.as_mut() {
+            Some(tui) => tui.show_resurfaced(&shown),
+            None => view::render_resurfaced(&shown),
+        }
+
+        let shareable: Vec<_> = shareable.into_iter().flatten().collect();
+        if self.resurfacing.share_with_coach && !private_mode && !shareable.is_empty() {
+            self.process_action(Action::ShareResurfaced(shareable))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Show current streaks and goal progress. Streaks are a nicety, so problems reading
+    /// entries are ignored.
+    fn show_streaks(&mut self) {
+        let today = chrono::Local::now().date_naive();
+        if let Ok(status) = StreakStatus::load(&self.effect_runner.vault_path, &self.streaks, today)
+        {
+            match self.tui.as_mut() {
+                Some(tui) => tui.notice_streaks(&status),
+                None => view::render_streaks(&status),
+            }
         }
     }
 
+    /// Show the current state, full-screen while the session is interactive. The full-screen
+    /// view is left for the analysis so that it stays on screen after the journal exits.
+    fn render(&mut self) -> Result<()> {
+        if let Some(tui) = self.tui.as_mut() {
+            if !self.state.is_terminal() && !matches!(self.state, State::AnalysisReady { .. }) {
+                return tui.draw(&self.state);
+            }
+            self.tui = None;
+        }
+
+        if matches!(self.state, State::PromptingForNew) {
+            self.show_streaks();
+        }
+        view::view(&self.state);
+        if matches!(self.state, State::Done(_)) {
+            self.show_streaks();
+        }
+        Ok(())
+    }
+
+    /// The next action from the user, or `None` if they quit from the full-screen view
+    async fn next_user_action(&mut self) -> Result<Option<Action>> {
+        let context = match self.state {
+            State::PromptingForNew => InputContext::ModeSelection,
+            State::InSession(_) => InputContext::InSession,
+            _ => InputContext::ModeSelection,
+        };
+        let action = match self.tui.as_mut() {
+            Some(tui) => match tui.next_action(&self.state)? {
+                Some(action) => action,
+                None => return Ok(None),
+            },
+            None => {
+                let input = self.get_user_input(&context).await?;
+                UserInput::new_with_context(input, context.clone()).processed
+            }
+        };
+
+        // Commands that only affect this screen are handled here rather than by update
+        match action {
+            Action::ComposeInEditor { revise_last } => {
+                self.compose_in_editor(revise_last).map(Some)
+            }
+            Action::Discard => {
+                if self.confirm("Delete this session and everything you wrote in it?")? {
+                    Ok(Some(action))
+                } else {
+                    Ok(Some(Action::NextQuestion))
+                }
+            }
+            Action::Help => {
+                match self.tui.as_mut() {
+                    Some(tui) => tui.flash(view::command_names()),
+                    None => view::render_help(),
+                }
+                Ok(Some(action))
+            }
+            action => {
+                if let Some(message) = view::acknowledgement(&action) {
+                    match self.tui.as_mut() {
+                        Some(tui) => tui.flash(message),
+                        None => println!("\n{message}"),
+                    }
+                }
+                Ok(Some(action))
+            }
+        }
+    }
+
+    /// Write the next response in the user's editor, or revise the last one. If the editor
+    /// can't be used, the problem is shown and the session carries on.
+    fn compose_in_editor(&mut self, revise_last: bool) -> Result<Action> {
+        let last_response = match (&self.state, revise_last) {
+            (State::InSession(session), true) => session
+                .get_user_responses()
+                .last()
+                .map(|entry| entry.content.clone()),
+            _ => None,
+        };
+
+        if let Some(tui) = self.tui.as_mut() {
+            tui.suspend()?;
+        }
+        let result = editor::compose(last_response.as_deref().unwrap_or_default());
+        if let Some(tui) = self.tui.as_mut() {
+            tui.resume()?;
+        }
+
+        match result {
+            Ok(text) if last_response.as_ref().is_some_and(|last| *last == text) => {
+                Ok(Action::NextQuestion)
+            }
+            Ok(text) if !text.is_empty() && last_response.is_some() => Ok(Action::ReviseLast(text)),
+            Ok(text) if !text.is_empty() => Ok(Action::UserResponse(text)),
+            Ok(_) => Ok(Action::NextQuestion),
+            Err(e) => {
+                let message = format!("Could not use your editor: {e:#}");
+                match self.tui.as_mut() {
+                    Some(tui) => tui.flash(message),
+                    None => eprintln!("\n❌ {message}"),
+                }
+                Ok(Action::NextQuestion)
+            }
+        }
+    }
+
+    /// Ask a yes/no question, leaving full-screen mode while it's answered. Without a
+    /// terminal the answer is read as a line of input.
+    fn confirm(&mut self, prompt: &str) -> Result<bool> {
+        if !io::stdin().is_terminal() {
+            println!("\n{prompt} (y/N)");
+            let mut answer = String::new();
+            io::stdin()
+                .read_line(&mut answer)
+                .context("Failed to read confirmation")?;
+            return Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"));
+        }
+
+        if let Some(tui) = self.tui.as_mut() {
+            tui.suspend()?;
+        }
+        let confirmed = dialoguer::Confirm::new()
+            .with_prompt(prompt)
+            .default(false)
+            .interact()
+            .context("Failed to read confirmation");
+        if let Some(tui) = self.tui.as_mut() {
+            tui.resume()?;
+        }
+        confirmed
+    }
+
+    fn is_private(&self, mode: SessionMode) -> bool {
+        self.effect_runner.local_only || self.private_modes.contains(&mode)
+    }
+
     async fn run(&mut self, initial_action: Action) -> Result<()> {
-        // Process initial action
-        self.process_action(initial_action).await?;
+        self.start(initial_action).await?;
 
         // Main interactive loop
         while !self.state.is_terminal() {
             if self.state.is_interactive() {
-                // Get user input
-                let input = self.get_user_input().await?;
-                let context = match self.state {
-                    State::PromptingForNew => InputContext::ModeSelection,
-                    State::InSession(_) => InputContext::InSession,
-                    _ => InputContext::ModeSelection,
+                let Some(action) = self.next_user_action().await? else {
+                    break;
                 };
-                let action = UserInput::new_with_context(input, context).processed;
+                let private_mode = match action {
+                    Action::SelectMode(mode) => self.is_private(mode),
+                    _ => false,
+                };
+                let starting_session = matches!(action, Action::SelectMode(_));
                 self.process_action(action).await?;
+
+                if starting_session {
+                    self.resurface_past_entries(private_mode).await?;
+                }
+
+                // Sessions in private modes switch to local-only before anything is sent,
+                // then start on the built-in questions
+                if private_mode {
+                    self.process_action(Action::MakePrivate).await?;
+                    self.process_action(Action::NextQuestion).await?;
+                }
             } else {
                 // Non-interactive states should have generated effects that will advance the state
                 // If we're stuck in a non-interactive state, that's an error
@@ -187,6 +998,9 @@ impl JournalApp {
             }
         }
 
+        // Restore the terminal before exiting
+        self.tui = None;
+
         // Exit with error code if we're in an error state
         if matches!(self.state, State::Error(_)) {
             std::process::exit(1);
@@ -195,66 +1009,61 @@ impl JournalApp {
         Ok(())
     }
 
+    /// Process the action the app was started with. A resumed session is switched to
+    /// local-only before anything else happens if it's in a private mode or `--private` is set.
+    async fn start(&mut self, initial_action: Action) -> Result<()> {
+        let resuming = matches!(initial_action, Action::Resume(_));
+        self.process_action(initial_action).await?;
+
+        if let (true, State::InSession(session)) = (resuming, &self.state) {
+            if self.is_private(session.mode) && !session.metadata.private {
+                self.process_action(Action::MakePrivate).await?;
+            }
+        }
+        if let (true, None, State::InSession(session)) = (resuming, &self.tui, &self.state) {
+            view::render_recap(session);
+        }
+        Ok(())
+    }
+
     async fn process_action(&mut self, action: Action) -> Result<()> {
-        let (new_state, effects) = update::update(self.state.clone(), action);
-        self.state = new_state;
+        // Effects may produce follow-up actions (coach responses, analysis, ...), which are
+        // queued and fed back through update until the chain settles
+        let mut pending = VecDeque::from([action]);
 
-        // Display the new state
-        view::view(&self.state);
+        while let Some(action) = pending.pop_front() {
+            let is_internal = action.is_internal();
+            let (new_state, effects) = update::update(self.state.clone(), action);
+            self.state = new_state;
 
-        // Execute all effects
-        for effect in effects {
-            let effect_for_match = effect.clone();
-            match self.effect_runner.run_effect(effect).await {
-                Ok(Some(resulting_action)) => {
-                    // Some effects generate new actions (like coach responses)
-                    let (next_state, next_effects) =
-                        update::update(self.state.clone(), resulting_action);
-                    self.state = next_state;
-
-                    // Display the updated state
-                    view::view(&self.state);
-
-                    // Execute any additional effects
-                    for next_effect in next_effects {
-                        match self.effect_runner.run_effect(next_effect).await {
-                            Ok(_) => {}
-                            Err(_) => {
-                                // Continue with the session instead of crashing
-                            }
-                        }
-                    }
-                }
-                Ok(None) => {
-                    // Effect completed successfully without generating an action
+            // Display the new state
+            if !is_internal {
+                self.render()?;
+            }
+
+            for effect in effects {
+                let is_analysis = matches!(effect, Effect::GenerateAnalysis { .. });
+                let is_save = matches!(effect, Effect::SaveSession(_));
+                let is_load = matches!(effect, Effect::LoadSession(_));
+                let result = self.effect_runner.run_effect(effect).await;
+                if let (true, Some(tui)) = (is_save, self.tui.as_mut()) {
+                    tui.save_state = match result {
+                        Ok(_) => SaveState::Saved,
+                        Err(_) => SaveState::Failed,
+                    };
                 }
-                Err(e) => {
-                    // Special handling for analysis errors - provide fallback behavior
-                    if matches!(effect_for_match, Effect::GenerateAnalysis { .. }) {
-                        // Generate a fallback AnalysisComplete action with error message
-                        let fallback_analysis = format!(
-                            "**AI Analysis Unavailable**\n\n\
-                            The AI analysis feature encountered an error and is currently unavailable. \
-                            Your journal session has been saved successfully.\n\n\
-                            Error details: {e}"
-                        );
-                        let fallback_action = Action::AnalysisComplete(fallback_analysis);
-                        let (next_state, next_effects) =
-                            update::update(self.state.clone(), fallback_action);
-                        self.state = next_state;
-
-                        // Display the updated state
-                        view::view(&self.state);
-
-                        // Execute any additional effects from the fallback
-                        for next_effect in next_effects {
-                            match self.effect_runner.run_effect(next_effect).await {
-                                Ok(_) => {}
-                                Err(_) => {
-                                    // Error in fallback effect, continue anyway
-                                }
-                            }
+                match result {
+                    Ok(Some(resulting_action)) => pending.push_back(resulting_action),
+                    Ok(None) => {
+                        // Effect completed successfully without generating an action
+                    }
+                    Err(e) if is_load => return Err(e.context("Could not resume the session")),
+                    Err(e) => {
+                        // Analysis errors save the entry now and queue the analysis for later
+                        if is_analysis {
+                            pending.push_back(Action::AnalysisFailed(e.to_string()));
                         }
+                        // Other effect errors are non-fatal; continue with the session
                     }
                 }
             }
@@ -263,13 +1072,8 @@ impl JournalApp {
         Ok(())
     }
 
-    async fn get_user_input(&mut self) -> Result<String> {
-        let mut line = String::new();
-        io::stdin()
-            .read_line(&mut line)
-            .context("Failed to read user input")?;
-
-        Ok(line.trim().to_string())
+    async fn get_user_input(&mut self, context: &InputContext) -> Result<String> {
+        UserInput::read(&mut io::stdin().lock(), context).context("Failed to read user input")
     }
 }
 
@@ -295,11 +1099,16 @@ mod tests {
         // Test default behavior
         let config = AppConfig {
             vault_path: get_default_vault_path(),
+            local_only: false,
+            tui: false,
+            read_only: false,
             command: AppCommand::New,
         };
 
         // This is a simple test - in practice you'd use clap's testing facilities
         assert!(matches!(config.command, AppCommand::New));
+        assert!(config.command.writes());
+        assert!(!AppCommand::Streak.writes());
 
         // Verify default path is ~/Documents/vault
         let expected_path = if let Some(home_dir) = std::env::var_os("HOME") {
@@ -313,6 +1122,22 @@ mod tests {
         assert_eq!(config.vault_path, expected_path);
     }
 
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(
+            parse_date("2024-03-01"),
+            Ok(NaiveDate::from_ymd_opt(2024, 3, 1).unwrap())
+        );
+        assert!(parse_date("March 1st").is_err());
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("Morning"), Ok(SessionMode::Morning));
+        assert_eq!(parse_mode("evening"), Ok(SessionMode::Evening));
+        assert!(parse_mode("noon").is_err());
+    }
+
     #[test]
     fn test_get_default_vault_path() {
         let default_path = get_default_vault_path();
@@ -326,13 +1151,46 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_resume_under_private_makes_session_private() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path().to_path_buf();
+        let mut app = JournalApp::new(vault_path.clone()).await.unwrap();
+
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(state::Speaker::User, "Paused halfway".to_string());
+        session.metadata.status = SessionStatus::Paused;
+        app.effect_runner
+            .run_effect(Effect::SaveSession(session))
+            .await
+            .unwrap();
+        let session_id = sessions::list_sessions(&vault_path).unwrap()[0]
+            .metadata
+            .session_doc_id
+            .unwrap();
+
+        app.effect_runner.local_only = true;
+        app.start(Action::Resume(session_id)).await.unwrap();
+
+        let State::InSession(session) = &app.state else {
+            panic!("expected the session to be resumed, got {:?}", app.state);
+        };
+        assert!(session.metadata.private);
+        assert!(
+            sessions::load_session(&vault_path, session_id)
+                .unwrap()
+                .metadata
+                .private
+        );
+    }
+
     #[tokio::test]
     async fn test_find_active_session_no_index() {
         let temp_dir = TempDir::new().unwrap();
         let vault_path = temp_dir.path().to_path_buf();
 
-        let app = JournalApp::new(vault_path).await.unwrap();
-        let result = app.find_active_session().await.unwrap();
+        let _app = JournalApp::new(vault_path.clone()).await.unwrap();
+        let result = find_active_session(&vault_path).await.unwrap();
 
         assert!(result.is_none());
     }
@@ -0,0 +1,417 @@
+use crate::config::MentionsConfig;
+use crate::entries::{self, EntryFilter};
+use crate::pack;
+use aethel_core::{Doc, Patch, PatchMode};
+use anyhow::{Context, Result};
+use chrono::NaiveDate;
+use serde_json::json;
+use std::path::Path;
+use uuid::Uuid;
+
+/// Capitalized words that are part of ordinary sentences rather than names
+const NOT_NAMES: &[&str] = &[
+    "a",
+    "an",
+    "and",
+    "april",
+    "august",
+    "but",
+    "december",
+    "february",
+    "friday",
+    "i",
+    "i'd",
+    "i'll",
+    "i'm",
+    "i've",
+    "it",
+    "january",
+    "july",
+    "june",
+    "march",
+    "may",
+    "monday",
+    "my",
+    "no",
+    "november",
+    "october",
+    "ok",
+    "okay",
+    "saturday",
+    "september",
+    "so",
+    "sunday",
+    "the",
+    "then",
+    "thursday",
+    "today",
+    "tomorrow",
+    "tuesday",
+    "we",
+    "wednesday",
+    "yes",
+    "yesterday",
+];
+
+/// Longest snippet shown for a mention
+const SNIPPET_CHARS: usize = 120;
+
+/// A name and the entries that mention it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Person {
+    pub name: String,
+    pub entries: usize,
+    pub last_mentioned: NaiveDate,
+    /// Average mood of those entries, from 1 (challenging) to 3 (positive)
+    pub average_mood: Option<f64>,
+}
+
+/// An entry that mentions a name, with the lines that mention it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mention {
+    pub entry_id: Uuid,
+    pub date: NaiveDate,
+    pub title: String,
+    pub mood: Option<String>,
+    pub snippets: Vec<String>,
+}
+
+/// The people, places and projects mentioned in `lines`: configured names and their aliases,
+/// then capitalized phrases. Each is reported once, under its configured name if it has one.
+pub fn extract<'a>(
+    lines: impl IntoIterator<Item = &'a str>,
+    config: &MentionsConfig,
+) -> Vec<String> {
+    let lines: Vec<&str> = lines.into_iter().collect();
+    let mut mentions: Vec<String> = Vec::new();
+    let mut add = |name: String| {
+        if !mentions.contains(&name) {
+            mentions.push(name);
+        }
+    };
+
+    for (name, aliases) in &config.aliases {
+        let forms = std::iter::once(name).chain(aliases);
+        if forms
+            .into_iter()
+            .any(|form| lines.iter().any(|line| contains_phrase(line, form)))
+        {
+            add(name.clone());
+        }
+    }
+
+    if config.detect_capitalized {
+        for phrase in lines.iter().flat_map(|line| capitalized_phrases(line)) {
+            let ignored = config
+                .ignore
+                .iter()
+                .any(|ignored| ignored.eq_ignore_ascii_case(&phrase));
+            if !ignored {
+                add(canonical(&phrase, config));
+            }
+        }
+    }
+    mentions
+}
+
+/// The configured name for `name` if it's one of its aliases, otherwise `name` itself
+pub fn canonical(name: &str, config: &MentionsConfig) -> String {
+    config
+        .aliases
+        .iter()
+        .find(|(canonical, aliases)| {
+            canonical.eq_ignore_ascii_case(name)
+                || aliases.iter().any(|alias| alias.eq_ignore_ascii_case(name))
+        })
+        .map_or_else(|| name.to_string(), |(canonical, _)| canonical.clone())
+}
+
+/// The mentions stored in an entry's frontmatter
+pub fn entry_mentions(entry: &Doc) -> Vec<String> {
+    entry
+        .frontmatter_extra
+        .get("mentions")
+        .and_then(|mentions| mentions.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|mention| mention.as_str().map(str::to_string))
+        .collect()
+}
+
+/// Everyone and everything mentioned across entries, most mentioned first
+pub fn people(vault_path: &Path, config: &MentionsConfig) -> Result<Vec<Person>> {
+    let mut people: Vec<(Person, Vec<f64>)> = Vec::new();
+    for entry in entries::find_entries(vault_path, &EntryFilter::default())? {
+        let date = entries::entry_date(&entry);
+        let mood = entries::mood_score(&entry);
+        let mut names: Vec<String> = entry_mentions(&entry)
+            .iter()
+            .map(|name| canonical(name, config))
+            .collect();
+        names.sort();
+        names.dedup();
+
+        for name in names {
+            let index = match people.iter().position(|(person, _)| person.name == name) {
+                Some(index) => index,
+                None => {
+                    let person = Person {
+                        name,
+                        entries: 0,
+                        last_mentioned: date,
+                        average_mood: None,
+                    };
+                    people.push((person, Vec::new()));
+                    people.len() - 1
+                }
+            };
+            let (person, moods) = &mut people[index];
+            person.entries += 1;
+            person.last_mentioned = person.last_mentioned.max(date);
+            moods.extend(mood);
+        }
+    }
+
+    let mut people: Vec<Person> = people
+        .into_iter()
+        .map(|(mut person, moods)| {
+            person.average_mood =
+                (!moods.is_empty()).then(|| moods.iter().sum::<f64>() / moods.len() as f64);
+            person
+        })
+        .collect();
+    people.sort_by(|a, b| b.entries.cmp(&a.entries).then_with(|| a.name.cmp(&b.name)));
+    Ok(people)
+}
+
+/// Every entry that mentions `name` (or one of its aliases), oldest first
+pub fn find(vault_path: &Path, name: &str, config: &MentionsConfig) -> Result<Vec<Mention>> {
+    let name = canonical(name, config);
+    let forms: Vec<&String> = std::iter::once(&name)
+        .chain(config.aliases.get(&name).into_iter().flatten())
+        .collect();
+
+    let mut mentions = Vec::new();
+    for entry in entries::find_entries(vault_path, &EntryFilter::default())? {
+        let mentioned = entry_mentions(&entry)
+            .iter()
+            .any(|mention| canonical(mention, config) == name);
+        if !mentioned {
+            continue;
+        }
+
+        let snippets = entries::user_lines(&entry.body)
+            .filter(|line| forms.iter().any(|form| contains_phrase(line, form)))
+            .map(snippet)
+            .collect();
+        mentions.push(Mention {
+            entry_id: entry.uuid,
+            date: entries::entry_date(&entry),
+            title: entries::entry_title(&entry),
+            mood: entry
+                .frontmatter_extra
+                .get("mood")
+                .and_then(|mood| mood.as_str())
+                .map(str::to_string),
+            snippets,
+        });
+    }
+    Ok(mentions)
+}
+
+/// Extract the mentions of every entry again, e.g. after the aliases change. Stored mentions
+/// that still appear in the entry, such as ones found by the AI backend, are kept. Returns how
+/// many entries changed.
+pub fn reindex(vault_path: &Path, config: &MentionsConfig) -> Result<usize> {
+    let mut changed = 0;
+    for entry in entries::find_entries(vault_path, &EntryFilter::default())? {
+        let lines: Vec<&str> = entries::user_lines(&entry.body).collect();
+        let stored = entry_mentions(&entry);
+        let kept = stored
+            .iter()
+            .filter(|name| lines.iter().any(|line| contains_phrase(line, name)))
+            .map(|name| canonical(name, config));
+
+        let mut mentions = extract(lines.iter().copied(), config);
+        for name in kept {
+            if !mentions.contains(&name) {
+                mentions.push(name);
+            }
+        }
+        if mentions != stored {
+            set_mentions(vault_path, entry.uuid, &mentions)?;
+            changed += 1;
+        }
+    }
+    Ok(changed)
+}
+
+fn set_mentions(vault_path: &Path, entry_id: Uuid, mentions: &[String]) -> Result<()> {
+    pack::apply_checked(
+        vault_path,
+        Patch {
+            uuid: Some(entry_id),
+            doc_type: None,
+            mode: PatchMode::MergeFrontmatter,
+            frontmatter: Some(json!({ "mentions": mentions })),
+            body: None,
+        },
+    )
+    .with_context(|| format!("Failed to update the mentions of {entry_id}"))?;
+    Ok(())
+}
+
+/// Runs of capitalized words, skipping a lone capitalized word that only starts a sentence
+fn capitalized_phrases(line: &str) -> Vec<String> {
+    let mut phrases = Vec::new();
+    let mut phrase: Vec<&str> = Vec::new();
+    let mut phrase_starts_sentence = false;
+    let mut sentence_start = true;
+
+    let mut finish = |phrase: &mut Vec<&str>, starts_sentence: bool| {
+        // Leading words like "The" or "My" aren't part of the name
+        while phrase
+            .first()
+            .is_some_and(|word| NOT_NAMES.contains(&word.to_lowercase().as_str()))
+        {
+            phrase.remove(0);
+        }
+        let lone_sentence_start = starts_sentence && phrase.len() == 1;
+        if !phrase.is_empty() && !lone_sentence_start {
+            phrases.push(phrase.join(" "));
+        }
+        phrase.clear();
+    };
+
+    for token in line.split_whitespace() {
+        let word = token.trim_matches(|c: char| !c.is_alphanumeric() && c != '\'');
+        let word = word
+            .strip_suffix("'s")
+            .or_else(|| word.strip_suffix("’s"))
+            .unwrap_or(word);
+        let capitalized = word.chars().next().is_some_and(char::is_uppercase)
+            && word.chars().any(char::is_lowercase);
+
+        if capitalized {
+            if phrase.is_empty() {
+                phrase_starts_sentence = sentence_start;
+            }
+            phrase.push(word);
+        } else {
+            finish(&mut phrase, phrase_starts_sentence);
+        }
+
+        // Punctuation after a word ends the phrase, and a full stop ends the sentence
+        if token.ends_with(|c: char| !c.is_alphanumeric() && c != '\'') {
+            finish(&mut phrase, phrase_starts_sentence);
+        }
+        sentence_start = token.ends_with(['.', '!', '?']);
+    }
+    finish(&mut phrase, phrase_starts_sentence);
+    phrases
+}
+
+/// Whether `phrase` appears in `line` as whole words, ignoring case
+fn contains_phrase(line: &str, phrase: &str) -> bool {
+    let line = line.to_lowercase();
+    let phrase = phrase.to_lowercase();
+    if phrase.is_empty() {
+        return false;
+    }
+    line.match_indices(&phrase).any(|(start, _)| {
+        let before = line[..start].chars().next_back();
+        let after = line[start + phrase.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+fn snippet(line: &str) -> String {
+    match line.char_indices().nth(SNIPPET_CHARS) {
+        Some((cutoff, _)) => format!("{}…", line[..cutoff].trim_end()),
+        None => line.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entries::ENTRY_DOC_TYPE;
+    use aethel_core::apply_patch;
+    use tempfile::TempDir;
+
+    fn config() -> MentionsConfig {
+        MentionsConfig {
+            aliases: [("Sarah".to_string(), vec!["Sare".to_string()])].into(),
+            ignore: vec!["Zoom".to_string()],
+            ..MentionsConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_extract_aliases_and_capitalized_phrases() {
+        let lines = [
+            "Had lunch with sare and Tom at the Blue Door.",
+            "Then a Zoom call about Project Atlas. Tired now.",
+            "Monday I'll call Tom's brother.",
+        ];
+        assert_eq!(
+            extract(lines, &config()),
+            vec!["Sarah", "Tom", "Blue Door", "Project Atlas"]
+        );
+
+        let without_heuristics = MentionsConfig {
+            detect_capitalized: false,
+            ..config()
+        };
+        assert_eq!(extract(lines, &without_heuristics), vec!["Sarah"]);
+    }
+
+    #[test]
+    fn test_people_and_mentions_use_stored_mentions() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+        for (mood, line, mentions) in [
+            ("positive", "Walked with Sare by the river", json!(["Sare"])),
+            (
+                "challenging",
+                "Argued with Sarah and Tom",
+                json!(["Sarah", "Tom"]),
+            ),
+        ] {
+            apply_patch(
+                vault_path,
+                Patch {
+                    uuid: None,
+                    doc_type: Some(ENTRY_DOC_TYPE.to_string()),
+                    mode: PatchMode::Create,
+                    frontmatter: Some(json!({
+                        "session_id": null,
+                        "mode": "Evening",
+                        "session_type": "evening",
+                        "title": "Evening Journal Entry",
+                        "mood": mood,
+                        "mentions": mentions,
+                    })),
+                    body: Some(format!("## Session Transcript\n\nYou: {line}\n")),
+                },
+            )
+            .unwrap();
+        }
+
+        let people = people(vault_path, &config()).unwrap();
+        assert_eq!(people[0].name, "Sarah");
+        assert_eq!(people[0].entries, 2);
+        assert_eq!(people[0].average_mood, Some(2.0));
+        assert_eq!(people[1].name, "Tom");
+
+        let mentions = find(vault_path, "sare", &config()).unwrap();
+        assert_eq!(mentions.len(), 2);
+        assert_eq!(mentions[0].mood.as_deref(), Some("positive"));
+        assert_eq!(mentions[0].snippets, vec!["Walked with Sare by the river"]);
+
+        // Reindexing stores the configured names
+        assert_eq!(reindex(vault_path, &config()).unwrap(), 1);
+        let entries = entries::find_entries(vault_path, &EntryFilter::default()).unwrap();
+        assert_eq!(entry_mentions(&entries[0]), vec!["Sarah"]);
+    }
+}
@@ -0,0 +1,347 @@
+use aethel_core::{apply_patch, read_doc, Patch, PatchMode, WriteResult};
+use anyhow::{Context, Result};
+use include_dir::{include_dir, Dir, DirEntry};
+use serde_json::Value;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Where packs are installed, relative to the vault root
+pub const PACKS_DIR: &str = "packs";
+
+/// Every version of the journal pack this build ships, one `journal@x.y.z` directory each
+static JOURNAL_PACKS: Dir = include_dir!("assets/packs");
+
+/// Version of an installed or embedded journal pack
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PackVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl PackVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse a pack directory name like `journal@0.2.0`
+    fn from_dir_name(name: &str) -> Option<Self> {
+        name.strip_prefix("journal@")?.parse().ok()
+    }
+}
+
+impl FromStr for PackVersion {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let parts = s
+            .split('.')
+            .map(|part| part.parse::<u32>())
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("Invalid pack version: {s}"))?;
+        match parts[..] {
+            [major, minor, patch] => Ok(Self::new(major, minor, patch)),
+            _ => anyhow::bail!("Invalid pack version: {s}"),
+        }
+    }
+}
+
+impl fmt::Display for PackVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Frontmatter that doesn't match its doc type's schema
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[error("{doc_type} frontmatter doesn't match its schema: {}", violations.join("; "))]
+pub struct SchemaError {
+    pub doc_type: String,
+    pub violations: Vec<String>,
+}
+
+/// Directory of a journal pack version, relative to the vault root
+pub fn pack_dir(version: PackVersion) -> String {
+    format!("{PACKS_DIR}/journal@{version}")
+}
+
+/// The newest journal pack this build ships
+pub fn current_version() -> PackVersion {
+    JOURNAL_PACKS
+        .dirs()
+        .filter_map(|dir| PackVersion::from_dir_name(dir.path().to_str()?))
+        .max()
+        .expect("at least one journal pack is embedded")
+}
+
+fn current_pack() -> &'static Dir<'static> {
+    let version = current_version();
+    JOURNAL_PACKS
+        .get_dir(format!("journal@{version}"))
+        .expect("the current journal pack is embedded")
+}
+
+/// The newest journal pack installed in the vault, if any
+pub fn installed_version(vault_path: &Path) -> Option<PackVersion> {
+    fs::read_dir(vault_path.join(PACKS_DIR))
+        .ok()?
+        .filter_map(|dir_entry| dir_entry.ok())
+        .filter(|dir_entry| dir_entry.path().join("pack.json").exists())
+        .filter_map(|dir_entry| PackVersion::from_dir_name(dir_entry.file_name().to_str()?))
+        .max()
+}
+
+/// Install the current journal pack into the vault, replacing any of its files already there.
+/// Older pack versions are left alongside it.
+pub fn install(vault_path: &Path) -> Result<()> {
+    let pack_path = vault_path.join(pack_dir(current_version()));
+    fs::create_dir_all(&pack_path).context("Failed to create pack directory")?;
+
+    // Files are stored relative to the packs root, so strip the version directory
+    let mut dirs = vec![current_pack()];
+    while let Some(dir) = dirs.pop() {
+        for entry in dir.entries() {
+            match entry {
+                DirEntry::Dir(child) => dirs.push(child),
+                DirEntry::File(file) => {
+                    let path = pack_path.join(relative_path(file.path()));
+                    if let Some(parent) = path.parent() {
+                        fs::create_dir_all(parent).context("Failed to create pack directory")?;
+                    }
+                    fs::write(&path, file.contents())
+                        .with_context(|| format!("Failed to write {}", path.display()))?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Files of the current pack that are missing from the vault or differ from the ones this
+/// version ships
+pub fn outdated_files(vault_path: &Path) -> Vec<String> {
+    let pack_path = vault_path.join(pack_dir(current_version()));
+    let mut outdated = Vec::new();
+    let mut dirs = vec![current_pack()];
+    while let Some(dir) = dirs.pop() {
+        for entry in dir.entries() {
+            match entry {
+                DirEntry::Dir(child) => dirs.push(child),
+                DirEntry::File(file) => {
+                    let path = relative_path(file.path());
+                    let installed = fs::read(pack_path.join(path)).ok();
+                    if installed.as_deref() != Some(file.contents()) {
+                        outdated.push(path.display().to_string());
+                    }
+                }
+            }
+        }
+    }
+    outdated.sort();
+    outdated
+}
+
+/// Path of an embedded file within its pack version directory
+fn relative_path(path: &Path) -> &Path {
+    let mut components = path.components();
+    components.next();
+    components.as_path()
+}
+
+/// The JSON schema this version ships for a doc type, e.g. `journal.entry`
+fn schema(doc_type: &str) -> Result<Option<Value>> {
+    let pack = current_pack();
+    let manifest: Value = serde_json::from_slice(
+        pack.get_file(pack.path().join("pack.json"))
+            .context("Journal pack has no pack.json")?
+            .contents(),
+    )
+    .context("Failed to parse pack.json")?;
+
+    let Some(schema_path) = manifest["types"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .find(|doc_type_entry| doc_type_entry["id"] == doc_type)
+        .and_then(|doc_type_entry| doc_type_entry["schema"].as_str())
+    else {
+        return Ok(None);
+    };
+
+    let file = pack
+        .get_file(pack.path().join(schema_path))
+        .with_context(|| format!("Journal pack is missing {schema_path}"))?;
+    serde_json::from_slice(file.contents())
+        .with_context(|| format!("Failed to parse {schema_path}"))
+        .map(Some)
+}
+
+/// Check a doc's frontmatter against its type's schema. Returns one message per violation;
+/// doc types outside the journal pack have nothing to check.
+pub fn validate(doc_type: &str, frontmatter: &Value) -> Result<Vec<String>> {
+    let Some(schema) = schema(doc_type)? else {
+        return Ok(Vec::new());
+    };
+    let validator = jsonschema::validator_for(&schema)
+        .map_err(|e| anyhow::anyhow!("Invalid schema for {doc_type}: {e}"))?;
+
+    Ok(validator
+        .iter_errors(frontmatter)
+        .map(|error| {
+            let location = error.instance_path.to_string();
+            if location.is_empty() {
+                error.to_string()
+            } else {
+                format!("{location}: {error}")
+            }
+        })
+        .collect())
+}
+
+/// Check frontmatter against its doc type's schema before it's written
+pub fn check(doc_type: &str, frontmatter: &Value) -> Result<()> {
+    let violations = validate(doc_type, frontmatter)?;
+    if !violations.is_empty() {
+        return Err(SchemaError {
+            doc_type: doc_type.to_string(),
+            violations,
+        }
+        .into());
+    }
+    Ok(())
+}
+
+/// Apply a patch, first checking the frontmatter it would leave the doc with against the
+/// doc type's schema. Nothing is written if it doesn't match.
+pub fn apply_checked(vault_path: &Path, patch: Patch) -> Result<WriteResult> {
+    let (doc_type, frontmatter) = match (&patch.mode, patch.uuid) {
+        (PatchMode::Create, _) => (
+            patch.doc_type.clone().unwrap_or_default(),
+            patch.frontmatter.clone().unwrap_or(Value::Null),
+        ),
+        (PatchMode::MergeFrontmatter, Some(uuid)) => {
+            let mut doc = read_doc(vault_path, &uuid).context("Failed to read document")?;
+            if let Some(Value::Object(changes)) = &patch.frontmatter {
+                doc.frontmatter_extra.extend(changes.clone());
+            }
+            (doc.doc_type, Value::Object(doc.frontmatter_extra))
+        }
+        // Body-only changes leave the frontmatter as it is
+        _ => return Ok(apply_patch(vault_path, patch)?),
+    };
+
+    check(&doc_type, &frontmatter)?;
+    Ok(apply_patch(vault_path, patch)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_install_and_detect_outdated_files() {
+        let temp_dir = TempDir::new().unwrap();
+        assert!(outdated_files(temp_dir.path()).contains(&"pack.json".to_string()));
+        assert_eq!(installed_version(temp_dir.path()), None);
+
+        // An older pack stays installed alongside the current one
+        let old_pack = temp_dir.path().join(pack_dir(PackVersion::new(0, 1, 0)));
+        fs::create_dir_all(&old_pack).unwrap();
+        fs::write(old_pack.join("pack.json"), "{}").unwrap();
+        assert_eq!(
+            installed_version(temp_dir.path()),
+            Some(PackVersion::new(0, 1, 0))
+        );
+
+        install(temp_dir.path()).unwrap();
+        assert!(outdated_files(temp_dir.path()).is_empty());
+        assert_eq!(installed_version(temp_dir.path()), Some(current_version()));
+        assert!(old_pack.join("pack.json").exists());
+
+        let schema_path = temp_dir
+            .path()
+            .join(pack_dir(current_version()))
+            .join("types/entry.schema.json");
+        fs::write(&schema_path, "{}").unwrap();
+        assert_eq!(
+            outdated_files(temp_dir.path()),
+            vec!["types/entry.schema.json".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_pack_versions_order_numerically() {
+        let version: PackVersion = "0.10.0".parse().unwrap();
+        assert!(version > PackVersion::new(0, 2, 0));
+        assert_eq!(version.to_string(), "0.10.0");
+        assert!("0.2".parse::<PackVersion>().is_err());
+        assert!(current_version() >= PackVersion::new(0, 2, 0));
+    }
+
+    #[test]
+    fn test_validate_against_pack_schemas() {
+        let entry = json!({
+            "session_id": null,
+            "mode": "Evening",
+            "session_type": "evening",
+            "title": "Evening Journal Entry",
+        });
+        assert!(validate("journal.entry", &entry).unwrap().is_empty());
+
+        let errors = validate("journal.entry", &json!({ "mode": "Noon" })).unwrap();
+        assert!(errors.iter().any(|error| error.contains("title")));
+        assert!(errors.iter().any(|error| error.starts_with("/mode")));
+
+        assert!(validate("other.type", &json!({})).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_apply_checked_rejects_invalid_frontmatter() {
+        let temp_dir = TempDir::new().unwrap();
+        let entry = |frontmatter| Patch {
+            uuid: None,
+            doc_type: Some("journal.entry".to_string()),
+            mode: PatchMode::Create,
+            frontmatter: Some(frontmatter),
+            body: Some("Entry".to_string()),
+        };
+
+        let error = apply_checked(temp_dir.path(), entry(json!({ "mode": "Noon" }))).unwrap_err();
+        let error = error.downcast_ref::<SchemaError>().unwrap();
+        assert_eq!(error.doc_type, "journal.entry");
+        assert!(!error.violations.is_empty());
+        assert!(!temp_dir.path().join("docs").exists());
+
+        let written = apply_checked(
+            temp_dir.path(),
+            entry(json!({
+                "session_id": null,
+                "mode": "Evening",
+                "session_type": "evening",
+                "title": "Evening Journal Entry",
+            })),
+        )
+        .unwrap();
+
+        // Merged changes are checked against the whole resulting frontmatter
+        let merge = Patch {
+            uuid: Some(written.uuid),
+            doc_type: None,
+            mode: PatchMode::MergeFrontmatter,
+            frontmatter: Some(json!({ "mode": "Noon" })),
+            body: None,
+        };
+        let error = apply_checked(temp_dir.path(), merge).unwrap_err();
+        assert!(error.downcast_ref::<SchemaError>().is_some());
+        let doc = read_doc(temp_dir.path(), &written.uuid).unwrap();
+        assert_eq!(doc.frontmatter_extra["mode"], "Evening");
+    }
+}
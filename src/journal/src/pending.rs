@@ -0,0 +1,133 @@
+use crate::index;
+use crate::state::JournalSession;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Location of the deferred analysis queue, relative to the vault root
+pub const PENDING_ANALYSES_FILE: &str = ".aethel/queue/journal.pending-analyses.json";
+
+/// An entry saved without its AI analysis because the backend was unavailable
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingAnalysis {
+    pub entry_id: Uuid,
+    /// The completed session, so the analysis can be generated without the session doc
+    pub session: JournalSession,
+    pub queued_at: DateTime<Utc>,
+    #[serde(default)]
+    pub attempts: u32,
+    #[serde(default)]
+    pub last_error: Option<String>,
+}
+
+impl PendingAnalysis {
+    pub fn new(entry_id: Uuid, session: JournalSession) -> Self {
+        Self {
+            entry_id,
+            session,
+            queued_at: Utc::now(),
+            attempts: 0,
+            last_error: None,
+        }
+    }
+}
+
+/// Persistent queue of analyses waiting for the AI backend
+pub struct PendingQueue {
+    path: PathBuf,
+}
+
+impl PendingQueue {
+    pub fn new(vault_path: &Path) -> Self {
+        Self {
+            path: vault_path.join(PENDING_ANALYSES_FILE),
+        }
+    }
+
+    pub fn load(&self) -> Result<Vec<PendingAnalysis>> {
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content =
+            fs::read_to_string(&self.path).context("Failed to read pending analysis queue")?;
+        serde_json::from_str(&content).context("Failed to parse pending analysis queue")
+    }
+
+    pub fn save(&self, pending: &[PendingAnalysis]) -> Result<()> {
+        if pending.is_empty() {
+            if self.path.exists() {
+                fs::remove_file(&self.path).context("Failed to remove pending analysis queue")?;
+            }
+            return Ok(());
+        }
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create queue directory")?;
+        }
+        // A crash mid-write must not lose the entries already queued
+        index::write_atomic(&self.path, &serde_json::to_string_pretty(pending)?)
+            .context("Failed to write pending analysis queue")
+    }
+
+    pub fn push(&self, analysis: PendingAnalysis) -> Result<()> {
+        let mut pending = self.load()?;
+        pending.retain(|existing| existing.entry_id != analysis.entry_id);
+        pending.push(analysis);
+        self.save(&pending)
+    }
+
+    pub fn remove(&self, entry_id: Uuid) -> Result<()> {
+        let mut pending = self.load()?;
+        let before = pending.len();
+        pending.retain(|existing| existing.entry_id != entry_id);
+        if pending.len() == before {
+            return Ok(());
+        }
+        self.save(&pending)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::SessionMode;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_push_load_and_clear() {
+        let temp_dir = TempDir::new().unwrap();
+        let queue = PendingQueue::new(temp_dir.path());
+        assert!(queue.load().unwrap().is_empty());
+
+        let entry_id = Uuid::new_v4();
+        let session = JournalSession::new(SessionMode::Evening);
+        queue
+            .push(PendingAnalysis::new(entry_id, session.clone()))
+            .unwrap();
+        // Re-queueing the same entry replaces it rather than duplicating it
+        queue
+            .push(PendingAnalysis::new(entry_id, session.clone()))
+            .unwrap();
+        queue
+            .push(PendingAnalysis::new(Uuid::new_v4(), session))
+            .unwrap();
+
+        let pending = queue.load().unwrap();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].entry_id, entry_id);
+        assert!(!temp_dir
+            .path()
+            .join(format!("{PENDING_ANALYSES_FILE}.tmp"))
+            .exists());
+
+        queue.remove(entry_id).unwrap();
+        assert_eq!(queue.load().unwrap().len(), 1);
+
+        queue.save(&[]).unwrap();
+        assert!(!temp_dir.path().join(PENDING_ANALYSES_FILE).exists());
+    }
+}
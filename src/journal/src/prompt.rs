@@ -0,0 +1,551 @@
+use crate::backend::estimate_tokens;
+use crate::state::{JournalSession, SessionMode, Speaker, TranscriptEntry, TranscriptSummary};
+use serde::{Deserialize, Serialize};
+
+/// Number of most recent transcript entries that are always sent verbatim
+pub const RECENT_ENTRIES: usize = 6;
+
+/// Marks where the start of a turn was cut off to fit the token budget
+const OMITTED: &str = "[earlier text omitted] ";
+
+/// Marks where digest material was cut off to fit the token budget
+const LATER_OMITTED: &str = "\n\n[Later entries omitted]";
+
+const COACH_TEMPLATE: &str = "{coaching_context}\n\n{resurfaced}Conversation so far:\n{history}\n\nLatest user response: {user_response}\n\nPlease respond as an empathetic coach with a follow-up question or reflection that helps deepen their self-awareness.";
+
+const SUMMARY_TEMPLATE: &str = "{previous_summary}Conversation turns to add:\n{turns}\n\n\
+    Write a concise summary of this journaling conversation so far. Preserve the feelings, \
+    events, people and intentions the user mentioned so a coach can continue the conversation \
+    without the full transcript. Respond with the summary only.";
+
+const ANALYSIS_TEMPLATE: &str = "Please analyze this {analysis_context} journal session and provide:\n\n\
+    1. **Key Insights**: What are the main themes and patterns you notice?\n\
+    2. **Emotional Journey**: How did the person's emotional state evolve?\n\
+    3. **Action Items**: What specific, actionable steps could they take based on this session?\n\
+    4. **Reflections**: What deeper questions or areas for future exploration emerged?\n\
+    5. **Summary**: A brief 2-3 sentence summary of the session\n\n\
+    Journal Session:\n{conversation}\n\n\
+    Provide a thoughtful, empathetic analysis that honors their vulnerability and supports their growth.";
+
+const DIGEST_TEMPLATE: &str = "Please write a {period} review of this person's journal.\n\n\
+    Statistics for the period:\n{stats}\n\n\
+    Journal entries:\n{entries}\n\n\
+    Write a warm, honest narrative synthesis of the period: recurring themes, how their mood and \
+    energy moved, progress on their intentions and action items, and one or two gentle \
+    suggestions for the next {period}. Respond in Markdown without a top-level heading.";
+
+const MENTIONS_TEMPLATE: &str = "List the people, places and projects this person mentions in \
+    their journal below, one per line, written the way they wrote them. Respond with the names \
+    only, or with NONE if there are none.\n\n\
+    Journal:\n{responses}";
+
+/// What a prompt is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PromptKind {
+    Coach,
+    Summary,
+    Analysis,
+    Digest,
+    Mentions,
+}
+
+/// A rendered prompt together with a hash of the template it was built from, so
+/// outputs can be traced back to the exact prompt wording that produced them
+#[derive(Debug, Clone, PartialEq)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub text: String,
+    pub template_hash: String,
+}
+
+/// Stable FNV-1a hash of the static parts of a prompt template
+pub fn template_hash(parts: &[&str]) -> String {
+    let hash = parts
+        .iter()
+        .flat_map(|part| part.bytes().chain(std::iter::once(0)))
+        .fold(0xcbf29ce484222325_u64, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        });
+    format!("{hash:016x}")
+}
+
+/// Substitute `{name}` placeholders in a single pass, so substituted user text is never
+/// itself treated as a placeholder
+fn render(template: &str, vars: &[(&str, &str)]) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let value = after.find('}').and_then(|end| {
+            vars.iter()
+                .find(|(name, _)| *name == &after[..end])
+                .map(|(_, value)| (end, *value))
+        });
+        match value {
+            Some((end, value)) => {
+                output.push_str(value);
+                rest = &after[end + 1..];
+            }
+            None => {
+                output.push('{');
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+
+    output
+}
+
+fn analysis_context(mode: SessionMode) -> &'static str {
+    match mode {
+        SessionMode::Morning => "morning reflections and intentions",
+        SessionMode::Evening => "evening reflections and insights",
+    }
+}
+
+/// Prompt asking the coach to respond to the latest user message, cut down to fit in
+/// `budget` tokens
+pub fn coach_prompt(session: &JournalSession, user_response: &str, budget: usize) -> Prompt {
+    let coaching_context = session.mode.get_coaching_context();
+    let resurfaced = resurfaced_context(session);
+    let render_with = |history: &str, user_response: &str| {
+        render(
+            COACH_TEMPLATE,
+            &[
+                ("coaching_context", coaching_context),
+                ("resurfaced", &resurfaced),
+                ("history", history),
+                ("user_response", user_response),
+            ],
+        )
+    };
+
+    // The latest response is sent on its own and again in the history, so it may use at most
+    // half of what the template leaves
+    let available = budget.saturating_sub(estimate_tokens(&render_with("", "")));
+    let user_response = keep_end(user_response, available / 2);
+    let history = budgeted_history(session, available - estimate_tokens(&user_response));
+
+    Prompt {
+        kind: PromptKind::Coach,
+        text: render_with(&history, &user_response),
+        template_hash: template_hash(&[COACH_TEMPLATE, coaching_context]),
+    }
+}
+
+/// Past entries the user was reminded of at session start, which the coach may gently
+/// refer back to
+fn resurfaced_context(session: &JournalSession) -> String {
+    if session.metadata.resurfaced.is_empty() {
+        return String::new();
+    }
+
+    let entries = session
+        .metadata
+        .resurfaced
+        .iter()
+        .map(|entry| format!("- {} ({}): {}", entry.label, entry.date, entry.snippet))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!(
+        "The user was reminded of these earlier journal entries. Refer back to them only if it \
+        feels natural:\n{entries}\n\n"
+    )
+}
+
+/// Prompt asking for an end-of-session analysis. Falls back to the rolling summary
+/// plus recent turns when the full transcript doesn't fit in `budget` tokens.
+pub fn analysis_prompt(session: &JournalSession, budget: usize) -> Prompt {
+    let context = analysis_context(session.mode);
+    let render_with = |conversation: &str| {
+        render(
+            ANALYSIS_TEMPLATE,
+            &[
+                ("analysis_context", context),
+                ("conversation", conversation),
+            ],
+        )
+    };
+
+    let available = budget.saturating_sub(estimate_tokens(&render_with("")));
+    let mut conversation = session.get_conversation_summary();
+    if estimate_tokens(&conversation) > available {
+        conversation = budgeted_history(session, available);
+    }
+
+    Prompt {
+        kind: PromptKind::Analysis,
+        text: render_with(&conversation),
+        template_hash: template_hash(&[ANALYSIS_TEMPLATE, context]),
+    }
+}
+
+/// Prompt asking for a narrative synthesis of a week, month or year of entries. Entry
+/// material is cut off once the prompt no longer fits in `budget` tokens.
+pub fn digest_prompt(period: &str, stats: &str, entries: &str, budget: usize) -> Prompt {
+    let render_with = |entries: &str| {
+        render(
+            DIGEST_TEMPLATE,
+            &[("period", period), ("stats", stats), ("entries", entries)],
+        )
+    };
+
+    let available = budget.saturating_sub(estimate_tokens(&render_with("")));
+    let entries = if estimate_tokens(entries) > available {
+        let cutoff = entries
+            .char_indices()
+            .nth((available * 4).saturating_sub(LATER_OMITTED.len()))
+            .map(|(index, _)| index)
+            .unwrap_or(entries.len());
+        format!("{}{LATER_OMITTED}", &entries[..cutoff])
+    } else {
+        entries.to_string()
+    };
+
+    Prompt {
+        kind: PromptKind::Digest,
+        text: render_with(&entries),
+        template_hash: template_hash(&[DIGEST_TEMPLATE]),
+    }
+}
+
+/// Prompt asking for the people, places and projects the user wrote about. The earliest
+/// responses are cut off when they don't fit in `budget` tokens.
+pub fn mentions_prompt(session: &JournalSession, budget: usize) -> Prompt {
+    let responses = session
+        .get_user_responses()
+        .iter()
+        .map(|entry| entry.content.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+    let available = budget.saturating_sub(estimate_tokens(&render(
+        MENTIONS_TEMPLATE,
+        &[("responses", "")],
+    )));
+
+    Prompt {
+        kind: PromptKind::Mentions,
+        text: render(
+            MENTIONS_TEMPLATE,
+            &[("responses", &keep_end(&responses, available))],
+        ),
+        template_hash: template_hash(&[MENTIONS_TEMPLATE]),
+    }
+}
+
+/// Format transcript entries as `user:`/`assistant:` lines, skipping system messages
+pub fn format_turns(entries: &[TranscriptEntry]) -> String {
+    entries
+        .iter()
+        .filter(|entry| matches!(entry.speaker, Speaker::User | Speaker::Coach))
+        .map(|entry| {
+            let role = match entry.speaker {
+                Speaker::User => "user",
+                Speaker::Coach => "assistant",
+                _ => "system",
+            };
+            format!("{}: {}", role, entry.content)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Number of leading entries already folded into the rolling summary
+fn entries_covered(session: &JournalSession) -> usize {
+    session
+        .metadata
+        .transcript_summary
+        .as_ref()
+        .map(|summary| summary.entries_covered.min(session.transcript.len()))
+        .unwrap_or(0)
+}
+
+/// Conversation history to send to the backend: the rolling summary (if any)
+/// followed by every turn it doesn't cover yet
+pub fn conversation_history(session: &JournalSession) -> String {
+    history_from(session, entries_covered(session))
+}
+
+/// The rolling summary (if any) followed by the turns from `start` on
+fn history_from(session: &JournalSession, start: usize) -> String {
+    let recent = format_turns(&session.transcript[start..]);
+
+    match &session.metadata.transcript_summary {
+        Some(TranscriptSummary { summary, .. }) if entries_covered(session) > 0 => {
+            format!("Summary of earlier conversation:\n{summary}\n\nMost recent turns:\n{recent}")
+        }
+        _ => recent,
+    }
+}
+
+/// Conversation history that fits in `budget` tokens. When the recent turns alone are too
+/// long, the oldest of them are dropped, and what's left is cut from its start.
+fn budgeted_history(session: &JournalSession, budget: usize) -> String {
+    let mut start = entries_covered(session);
+    let mut history = history_from(session, start);
+    while estimate_tokens(&history) > budget && start + 1 < session.transcript.len() {
+        start += 1;
+        history = history_from(session, start);
+    }
+    keep_end(&history, budget)
+}
+
+/// The end of `text`, cut from its start so it fits in `budget` tokens
+fn keep_end(text: &str, budget: usize) -> String {
+    if estimate_tokens(text) <= budget {
+        return text.to_string();
+    }
+
+    let chars = budget * 4;
+    let marker = match chars > OMITTED.len() * 2 {
+        true => OMITTED,
+        false => "",
+    };
+    let skip = text.chars().count() - (chars - marker.len());
+    let start = text
+        .char_indices()
+        .nth(skip)
+        .map_or(text.len(), |(index, _)| index);
+    format!("{marker}{}", &text[start..])
+}
+
+/// If the history no longer fits in `budget` tokens, returns how many leading
+/// entries should be summarized (everything except the most recent turns)
+pub fn summary_cutoff(session: &JournalSession, budget: usize) -> Option<usize> {
+    if estimate_tokens(&conversation_history(session)) <= budget {
+        return None;
+    }
+
+    let cutoff = session.transcript.len().saturating_sub(RECENT_ENTRIES);
+    (cutoff > entries_covered(session)).then_some(cutoff)
+}
+
+/// Prompt asking the backend to fold entries up to `through` into the rolling summary
+pub fn summarization_prompt(session: &JournalSession, through: usize) -> Prompt {
+    let covered = entries_covered(session);
+    let previous = session
+        .metadata
+        .transcript_summary
+        .as_ref()
+        .filter(|_| covered > 0)
+        .map(|summary| format!("Existing summary:\n{}\n\n", summary.summary))
+        .unwrap_or_default();
+    let turns = format_turns(&session.transcript[covered..through.min(session.transcript.len())]);
+
+    Prompt {
+        kind: PromptKind::Summary,
+        text: render(
+            SUMMARY_TEMPLATE,
+            &[("previous_summary", &previous), ("turns", &turns)],
+        ),
+        template_hash: template_hash(&[SUMMARY_TEMPLATE]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::SessionMode;
+
+    fn long_session(turns: usize) -> JournalSession {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(
+            Speaker::System,
+            "Starting evening journal session".to_string(),
+        );
+        for i in 0..turns {
+            session.add_entry(
+                Speaker::User,
+                format!("User message {i} {}", "x".repeat(200)),
+            );
+            session.add_entry(Speaker::Coach, format!("Coach reply {i}?"));
+        }
+        session
+    }
+
+    #[test]
+    fn test_format_turns_skips_system() {
+        let session = long_session(1);
+        let formatted = format_turns(&session.transcript);
+        assert!(formatted.starts_with("user: User message 0"));
+        assert!(formatted.ends_with("assistant: Coach reply 0?"));
+        assert!(!formatted.contains("Starting evening"));
+    }
+
+    #[test]
+    fn test_summary_cutoff_within_budget() {
+        let session = long_session(3);
+        assert_eq!(summary_cutoff(&session, 10_000), None);
+    }
+
+    #[test]
+    fn test_summary_cutoff_keeps_recent_entries() {
+        let session = long_session(20);
+        let cutoff = summary_cutoff(&session, 100).unwrap();
+        assert_eq!(cutoff, session.transcript.len() - RECENT_ENTRIES);
+    }
+
+    #[test]
+    fn test_prompts_fit_budget_when_one_turn_exceeds_it() {
+        let mut session = long_session(2);
+        let huge = format!("start {} the end", "y".repeat(20_000));
+        session.add_entry(Speaker::User, huge.clone());
+
+        // The recent turns alone are over budget, so summarizing can't help
+        assert_eq!(summary_cutoff(&session, 1_000), None);
+
+        let coach = coach_prompt(&session, &huge, 1_000);
+        assert!(estimate_tokens(&coach.text) <= 1_000);
+        assert!(coach.text.contains(OMITTED));
+        assert!(!coach.text.contains("User message 0 "));
+        assert!(!coach.text.contains("start y"));
+        assert!(coach.text.contains("yy the end"));
+
+        let analysis = analysis_prompt(&session, 1_000);
+        assert!(estimate_tokens(&analysis.text) <= 1_000);
+        assert!(analysis.text.contains("yy the end"));
+    }
+
+    #[test]
+    fn test_history_uses_stored_summary() {
+        let mut session = long_session(20);
+        let cutoff = session.transcript.len() - RECENT_ENTRIES;
+        session.metadata.transcript_summary = Some(TranscriptSummary {
+            summary: "They had a long day.".to_string(),
+            entries_covered: cutoff,
+        });
+
+        let history = conversation_history(&session);
+        assert!(history.contains("They had a long day."));
+        assert!(!history.contains("User message 0 "));
+        assert!(history.contains("User message 19"));
+
+        // Summary is not recomputed while nothing new needs folding in
+        assert_eq!(summary_cutoff(&session, 100), None);
+    }
+
+    #[test]
+    fn test_render_is_single_pass() {
+        let rendered = render(
+            "{a} and {b} and {unknown}",
+            &[("a", "{b}"), ("b", "second")],
+        );
+        assert_eq!(rendered, "{b} and second and {unknown}");
+    }
+
+    #[test]
+    fn test_template_hash_is_stable() {
+        assert_eq!(template_hash(&["abc"]), template_hash(&["abc"]));
+        assert_ne!(template_hash(&["abc"]), template_hash(&["abd"]));
+        assert_ne!(template_hash(&["ab", "c"]), template_hash(&["a", "bc"]));
+        assert_eq!(template_hash(&[]).len(), 16);
+    }
+
+    #[test]
+    fn test_coach_prompt() {
+        let session = long_session(1);
+        let prompt = coach_prompt(&session, "I'm tired", 10_000);
+
+        assert_eq!(prompt.kind, PromptKind::Coach);
+        assert!(prompt
+            .text
+            .starts_with(SessionMode::Evening.get_coaching_context()));
+        assert!(prompt.text.contains("Latest user response: I'm tired"));
+        assert!(!prompt.text.contains("reminded of"));
+        assert_ne!(
+            prompt.template_hash,
+            coach_prompt(&JournalSession::new(SessionMode::Morning), "x", 10_000).template_hash
+        );
+        assert_eq!(
+            prompt.template_hash,
+            coach_prompt(&long_session(3), "other", 10_000).template_hash
+        );
+    }
+
+    #[test]
+    fn test_coach_prompt_includes_resurfaced_entries() {
+        let mut session = long_session(1);
+        session.metadata.resurfaced = vec![crate::state::ResurfacedEntry {
+            entry_id: uuid::Uuid::new_v4(),
+            label: "One year ago today".to_string(),
+            date: chrono::NaiveDate::from_ymd_opt(2023, 3, 14).unwrap(),
+            snippet: "Started the new job".to_string(),
+        }];
+
+        let prompt = coach_prompt(&session, "I'm tired", 10_000);
+        assert!(prompt
+            .text
+            .contains("- One year ago today (2023-03-14): Started the new job"));
+    }
+
+    #[test]
+    fn test_analysis_prompt_respects_budget() {
+        let session = long_session(20);
+        let full = analysis_prompt(&session, 100_000);
+        assert!(full.text.contains("User message 0 "));
+
+        let mut summarized = session.clone();
+        summarized.metadata.transcript_summary = Some(TranscriptSummary {
+            summary: "Earlier summary".to_string(),
+            entries_covered: 30,
+        });
+        let budgeted = analysis_prompt(&summarized, 1_000);
+        assert!(estimate_tokens(&budgeted.text) <= 1_000);
+        assert!(budgeted.text.contains("Earlier summary"));
+        assert!(!budgeted.text.contains("User message 0 "));
+        assert_eq!(full.template_hash, budgeted.template_hash);
+    }
+
+    #[test]
+    fn test_digest_prompt_truncates_entries() {
+        let entries = "x".repeat(10_000);
+        let stats = format!("- Sessions: 3\n- Themes: {}", "work ".repeat(100));
+        let prompt = digest_prompt("week", &stats, &entries, 500);
+
+        assert_eq!(prompt.kind, PromptKind::Digest);
+        assert!(prompt.text.starts_with("Please write a week review"));
+        assert!(prompt.text.contains(&stats));
+        assert!(prompt.text.contains("[Later entries omitted]"));
+        assert!(prompt.text.contains(&"x".repeat(100)));
+        assert!(estimate_tokens(&prompt.text) <= 500);
+    }
+
+    #[test]
+    fn test_mentions_prompt_only_includes_user_responses() {
+        let prompt = mentions_prompt(&long_session(2), 10_000);
+
+        assert_eq!(prompt.kind, PromptKind::Mentions);
+        assert!(prompt.text.contains("User message 1 "));
+        assert!(!prompt.text.contains("Coach reply"));
+        assert!(!prompt.text.contains("Starting evening journal session"));
+    }
+
+    #[test]
+    fn test_mentions_prompt_fits_budget() {
+        let session = long_session(200);
+        let prompt = mentions_prompt(&session, 1_000);
+
+        assert!(estimate_tokens(&prompt.text) <= 1_000);
+        assert!(prompt.text.contains(OMITTED));
+        assert!(!prompt.text.contains("User message 0 "));
+        assert!(prompt.text.contains("User message 199 "));
+    }
+
+    #[test]
+    fn test_summarization_prompt_includes_previous_summary() {
+        let mut session = long_session(10);
+        session.metadata.transcript_summary = Some(TranscriptSummary {
+            summary: "Earlier summary".to_string(),
+            entries_covered: 5,
+        });
+
+        let prompt = summarization_prompt(&session, 11).text;
+        assert!(prompt.contains("Existing summary:\nEarlier summary"));
+        assert!(!prompt.contains("User message 1 "));
+        assert!(prompt.contains("User message 2 "));
+        assert!(!prompt.contains("User message 5 "));
+    }
+}
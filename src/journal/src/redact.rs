@@ -0,0 +1,252 @@
+use crate::config::RedactionConfig;
+use anyhow::{Context, Result};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+const EMAIL_PATTERN: &str = r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}";
+const PHONE_PATTERN: &str = r"(?:\+\d{1,3}[\s.-]?)?(?:\(\d{3}\)|\b\d{3})[\s.-]?\d{3}[\s.-]?\d{4}\b";
+const ADDRESS_PATTERN: &str = r"\b\d{1,5}\s+(?:[A-Z][a-z]+\s+){1,3}(?:Street|St|Avenue|Ave|Road|Rd|Boulevard|Blvd|Lane|Ln|Drive|Dr|Court|Ct|Way|Place|Pl)\b\.?";
+
+/// The kind of sensitive text a placeholder stands in for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedactionKind {
+    Person,
+    Email,
+    Phone,
+    Address,
+    Custom,
+}
+
+impl RedactionKind {
+    fn label(&self) -> &'static str {
+        match self {
+            RedactionKind::Person => "Person",
+            RedactionKind::Email => "Email",
+            RedactionKind::Phone => "Phone",
+            RedactionKind::Address => "Address",
+            RedactionKind::Custom => "Redacted",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Redaction {
+    pub kind: RedactionKind,
+    pub placeholder: String,
+    pub original: String,
+}
+
+/// Reversible mapping between placeholders and the original text they replaced
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RedactionMap {
+    pub redactions: Vec<Redaction>,
+}
+
+impl RedactionMap {
+    /// Placeholder for `original`, reusing the existing one so replacements stay consistent
+    fn placeholder_for(&mut self, kind: RedactionKind, original: &str) -> String {
+        if let Some(existing) = self
+            .redactions
+            .iter()
+            .find(|r| r.kind == kind && r.original.eq_ignore_ascii_case(original))
+        {
+            return existing.placeholder.clone();
+        }
+
+        let index = self.redactions.iter().filter(|r| r.kind == kind).count();
+        let placeholder = format!("[{} {}]", kind.label(), placeholder_suffix(index));
+        self.redactions.push(Redaction {
+            kind,
+            placeholder: placeholder.clone(),
+            original: original.to_string(),
+        });
+        placeholder
+    }
+
+    /// Replace placeholders in `text` with the original values
+    pub fn restore(&self, text: &str) -> String {
+        self.redactions.iter().fold(text.to_string(), |acc, r| {
+            acc.replace(&r.placeholder, &r.original)
+        })
+    }
+}
+
+/// A, B, ..., Z, AA, AB, ...
+fn placeholder_suffix(mut index: usize) -> String {
+    let mut suffix = String::new();
+    loop {
+        suffix.insert(0, (b'A' + (index % 26) as u8) as char);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    suffix
+}
+
+/// Scrubs personal information from text before it leaves the machine
+#[derive(Debug, Clone)]
+pub struct Redactor {
+    rules: Vec<(RedactionKind, Regex)>,
+}
+
+impl Default for Redactor {
+    fn default() -> Self {
+        Self::from_config(&RedactionConfig::default())
+            .expect("built-in redaction patterns are valid")
+    }
+}
+
+impl Redactor {
+    pub fn from_config(config: &RedactionConfig) -> Result<Self> {
+        let mut rules = Vec::new();
+        if !config.enabled {
+            return Ok(Self { rules });
+        }
+
+        // Structured patterns run before names so e.g. "sarah@example.com" is one email
+        if config.detect_emails {
+            rules.push((RedactionKind::Email, Regex::new(EMAIL_PATTERN)?));
+        }
+        if config.detect_phones {
+            rules.push((RedactionKind::Phone, Regex::new(PHONE_PATTERN)?));
+        }
+        if config.detect_addresses {
+            rules.push((RedactionKind::Address, Regex::new(ADDRESS_PATTERN)?));
+        }
+        for pattern in &config.patterns {
+            let regex = Regex::new(pattern)
+                .with_context(|| format!("Invalid redaction pattern: {pattern}"))?;
+            rules.push((RedactionKind::Custom, regex));
+        }
+        for name in config.names.iter().filter(|name| !name.trim().is_empty()) {
+            let regex = Regex::new(&format!(r"(?i)\b{}\b", regex::escape(name.trim())))?;
+            rules.push((RedactionKind::Person, regex));
+        }
+
+        Ok(Self { rules })
+    }
+
+    /// Redact `text`, recording every replacement in `map`
+    pub fn redact(&self, text: &str, map: &mut RedactionMap) -> String {
+        self.rules
+            .iter()
+            .fold(text.to_string(), |acc, (kind, regex)| {
+                regex
+                    .replace_all(&acc, |caps: &regex::Captures| {
+                        map.placeholder_for(*kind, &caps[0])
+                    })
+                    .into_owned()
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redactor_with_names(names: &[&str]) -> Redactor {
+        Redactor::from_config(&RedactionConfig {
+            names: names.iter().map(|n| n.to_string()).collect(),
+            ..RedactionConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_names_are_replaced_consistently() {
+        let redactor = redactor_with_names(&["Sarah", "Tom"]);
+        let mut map = RedactionMap::default();
+
+        let redacted = redactor.redact(
+            "Sarah called. Then Tom and sarah went out. Sarahs is not a name.",
+            &mut map,
+        );
+
+        assert_eq!(
+            redacted,
+            "[Person A] called. Then [Person B] and [Person A] went out. Sarahs is not a name."
+        );
+        assert_eq!(map.redactions.len(), 2);
+    }
+
+    #[test]
+    fn test_detects_emails_phones_and_addresses() {
+        let redactor = redactor_with_names(&["Sarah"]);
+        let mut map = RedactionMap::default();
+
+        let redacted = redactor.redact(
+            "Email sarah@example.com or call 555-123-4567, I live at 42 Maple Street.",
+            &mut map,
+        );
+
+        assert_eq!(
+            redacted,
+            "Email [Email A] or call [Phone A], I live at [Address A]"
+        );
+    }
+
+    #[test]
+    fn test_custom_patterns() {
+        let redactor = Redactor::from_config(&RedactionConfig {
+            patterns: vec![r"Project \w+".to_string()],
+            ..RedactionConfig::default()
+        })
+        .unwrap();
+        let mut map = RedactionMap::default();
+
+        let redacted = redactor.redact("Project Falcon is late", &mut map);
+        assert_eq!(redacted, "[Redacted A] is late");
+    }
+
+    #[test]
+    fn test_invalid_custom_pattern_fails() {
+        let result = Redactor::from_config(&RedactionConfig {
+            patterns: vec!["(unclosed".to_string()],
+            ..RedactionConfig::default()
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_restore_round_trip() {
+        let redactor = redactor_with_names(&["Sarah"]);
+        let mut map = RedactionMap::default();
+        let original = "Sarah said to email sarah@example.com";
+
+        let redacted = redactor.redact(original, &mut map);
+        assert!(!redacted.contains("Sarah"));
+
+        let response = "It sounds like [Person A] cares about you.";
+        assert_eq!(
+            map.restore(response),
+            "It sounds like Sarah cares about you."
+        );
+        assert_eq!(map.restore(&redacted), original);
+    }
+
+    #[test]
+    fn test_disabled_redactor_is_identity() {
+        let redactor = Redactor::from_config(&RedactionConfig {
+            enabled: false,
+            names: vec!["Sarah".to_string()],
+            ..RedactionConfig::default()
+        })
+        .unwrap();
+        let mut map = RedactionMap::default();
+
+        assert_eq!(
+            redactor.redact("Sarah 555-123-4567", &mut map),
+            "Sarah 555-123-4567"
+        );
+        assert!(map.redactions.is_empty());
+    }
+
+    #[test]
+    fn test_placeholder_suffix() {
+        assert_eq!(placeholder_suffix(0), "A");
+        assert_eq!(placeholder_suffix(25), "Z");
+        assert_eq!(placeholder_suffix(26), "AA");
+        assert_eq!(placeholder_suffix(27), "AB");
+    }
+}
@@ -0,0 +1,166 @@
+use crate::entries::{self, EntryFilter};
+use crate::state::ResurfacedEntry;
+use aethel_core::Doc;
+use anyhow::Result;
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use std::path::Path;
+
+/// How many years back to look for entries written on the same date
+const MAX_YEARS_BACK: i32 = 10;
+
+/// Longest snippet shown for a resurfaced entry, in characters
+const SNIPPET_LENGTH: usize = 140;
+
+/// Dates worth looking back to from `today`, with how they're described, most recent first
+fn lookback_dates(today: NaiveDate) -> Vec<(String, NaiveDate)> {
+    let mut dates = vec![("One week ago".to_string(), today - Duration::weeks(1))];
+    if let Some(month_ago) = today.checked_sub_months(Months::new(1)) {
+        dates.push(("One month ago".to_string(), month_ago));
+    }
+    for years in 1..=MAX_YEARS_BACK {
+        // February 29th only comes around in leap years
+        if let Some(date) = today.with_year(today.year() - years) {
+            let label = match years {
+                1 => "One year ago today".to_string(),
+                n => format!("{n} years ago today"),
+            };
+            dates.push((label, date));
+        }
+    }
+    dates
+}
+
+/// The first thing the user wrote in an entry, shortened for display
+fn snippet(entry: &Doc) -> Option<String> {
+    let first = entries::user_lines(&entry.body).find(|line| !line.trim().is_empty())?;
+    let mut snippet: String = first.chars().take(SNIPPET_LENGTH).collect();
+    if first.chars().count() > SNIPPET_LENGTH {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+/// Past entries from one week, one month and whole years before `today`
+pub fn find_resurfaced(entries: &[Doc], today: NaiveDate) -> Vec<ResurfacedEntry> {
+    lookback_dates(today)
+        .into_iter()
+        .filter_map(|(label, date)| {
+            let entry = entries
+                .iter()
+                .find(|entry| entries::entry_date(entry) == date)?;
+            Some(ResurfacedEntry {
+                entry_id: entry.uuid,
+                label,
+                date,
+                snippet: snippet(entry)?,
+            })
+        })
+        .collect()
+}
+
+/// Entries to resurface from the vault on `today`, along with whether each may be shared
+/// with the AI coach
+pub fn load_resurfaced(
+    vault_path: &Path,
+    today: NaiveDate,
+) -> Result<Vec<(ResurfacedEntry, bool)>> {
+    let oldest = lookback_dates(today)
+        .iter()
+        .map(|(_, date)| *date)
+        .min()
+        .unwrap_or(today);
+    let candidates = entries::find_entries(
+        vault_path,
+        &EntryFilter {
+            since: Some(oldest),
+            ..EntryFilter::default()
+        },
+    )?;
+
+    Ok(find_resurfaced(&candidates, today)
+        .into_iter()
+        .map(|resurfaced| {
+            let shareable = candidates
+                .iter()
+                .find(|entry| entry.uuid == resurfaced.entry_id)
+                .is_some_and(entries::shareable_with_ai);
+            (resurfaced, shareable)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Local, TimeZone, Utc};
+    use serde_json::Map;
+    use uuid::Uuid;
+
+    fn entry_on(date: NaiveDate, body: &str) -> Doc {
+        let created = Local
+            .from_local_datetime(&date.and_hms_opt(12, 0, 0).unwrap())
+            .unwrap()
+            .with_timezone(&Utc);
+        Doc {
+            uuid: Uuid::new_v4(),
+            doc_type: entries::ENTRY_DOC_TYPE.to_string(),
+            created,
+            updated: created,
+            v: "1.0.0".to_string(),
+            tags: Vec::new(),
+            frontmatter_extra: Map::new(),
+            body: body.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_find_resurfaced_entries() {
+        let today = NaiveDate::from_ymd_opt(2024, 3, 14).unwrap();
+        let entries = vec![
+            entry_on(
+                NaiveDate::from_ymd_opt(2022, 3, 14).unwrap(),
+                "You: Started the new job",
+            ),
+            entry_on(
+                NaiveDate::from_ymd_opt(2024, 3, 7).unwrap(),
+                "You: Tired after the trip",
+            ),
+            entry_on(
+                NaiveDate::from_ymd_opt(2024, 3, 10).unwrap(),
+                "You: Not an anniversary",
+            ),
+        ];
+
+        let resurfaced = find_resurfaced(&entries, today);
+        assert_eq!(resurfaced.len(), 2);
+        assert_eq!(resurfaced[0].label, "One week ago");
+        assert_eq!(resurfaced[0].snippet, "Tired after the trip");
+        assert_eq!(resurfaced[1].label, "2 years ago today");
+        assert_eq!(resurfaced[1].entry_id, entries[0].uuid);
+    }
+
+    #[test]
+    fn test_snippet_is_shortened() {
+        let long = format!("You: {}", "a".repeat(200));
+        let entry = entry_on(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(), &long);
+        let snippet = snippet(&entry).unwrap();
+        assert_eq!(snippet.chars().count(), SNIPPET_LENGTH + 1);
+        assert!(snippet.ends_with('…'));
+
+        let empty = entry_on(
+            NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            "No transcript",
+        );
+        assert_eq!(super::snippet(&empty), None);
+    }
+
+    #[test]
+    fn test_leap_day_skips_missing_anniversaries() {
+        let leap_day = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let dates = lookback_dates(leap_day);
+        assert!(dates
+            .iter()
+            .any(|(_, date)| *date == NaiveDate::from_ymd_opt(2020, 2, 29).unwrap()));
+        assert!(!dates.iter().any(|(_, date)| date.year() == 2023));
+    }
+}
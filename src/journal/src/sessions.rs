@@ -0,0 +1,279 @@
+use crate::pack;
+use crate::state::{JournalSession, SessionMode, SessionStatus, Speaker};
+use aethel_core::{read_doc, Patch, PatchMode};
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::path::Path;
+use uuid::Uuid;
+
+pub const SESSION_DOC_TYPE: &str = "journal.session";
+
+/// Read a session doc back into a `JournalSession`
+pub fn load_session(vault_path: &Path, session_id: Uuid) -> Result<JournalSession> {
+    let doc = read_doc(vault_path, &session_id).context("Failed to load session document")?;
+    if doc.doc_type != SESSION_DOC_TYPE {
+        anyhow::bail!("{session_id} is a {}, not a journal session", doc.doc_type);
+    }
+
+    // Parse the transcript from the body
+    let transcript =
+        serde_json::from_str(&doc.body).context("Failed to parse session transcript")?;
+
+    // Extract session data from frontmatter
+    let session_data = &doc.frontmatter_extra;
+    let metadata = session_data.get("metadata").cloned().unwrap_or(json!({}));
+    let has_status = metadata.get("status").is_some();
+    let mut session = JournalSession {
+        mode: serde_json::from_value(
+            session_data
+                .get("mode")
+                .cloned()
+                .unwrap_or(json!("Morning")),
+        )
+        .unwrap_or(SessionMode::Morning),
+        transcript,
+        metadata: serde_json::from_value(metadata).context("Failed to parse session metadata")?,
+    };
+    session.metadata.session_doc_id = Some(session_id);
+    if !has_status {
+        session.metadata.status = legacy_status(&session);
+    }
+
+    Ok(session)
+}
+
+/// The status of a session saved before sessions recorded one
+fn legacy_status(session: &JournalSession) -> SessionStatus {
+    if session.metadata.final_entry_id.is_some() {
+        SessionStatus::Archived
+    } else if session.metadata.completed_at.is_some() {
+        SessionStatus::Completed
+    } else {
+        SessionStatus::Active
+    }
+}
+
+/// Every session doc in the vault, most recently updated first
+pub fn list_sessions(vault_path: &Path) -> Result<Vec<JournalSession>> {
+    let docs_dir = vault_path.join("docs");
+    if !docs_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions = Vec::new();
+    for dir_entry in std::fs::read_dir(&docs_dir).context("Failed to read docs directory")? {
+        let path = dir_entry?.path();
+        let Some(uuid) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| Uuid::parse_str(stem).ok())
+        else {
+            continue;
+        };
+
+        // Other doc types and unreadable sessions are skipped
+        if let Ok(session) = load_session(vault_path, uuid) {
+            sessions.push(session);
+        }
+    }
+
+    sessions.sort_by_key(|session| {
+        std::cmp::Reverse(session.transcript.last().map(|entry| entry.timestamp))
+    });
+    Ok(sessions)
+}
+
+/// Sessions that can be picked up again, most recently updated first: paused sessions, and
+/// the index's active session if the journal exited without finishing it
+pub fn find_resumable(vault_path: &Path, active: Option<Uuid>) -> Result<Vec<JournalSession>> {
+    let mut sessions = list_sessions(vault_path)?;
+    sessions.retain(|session| match session.metadata.status {
+        _ if session.metadata.has_ended() => false,
+        SessionStatus::Paused => true,
+        SessionStatus::Active => active.is_some() && active == session.metadata.session_doc_id,
+        SessionStatus::Completed | SessionStatus::Abandoned | SessionStatus::Archived => false,
+    });
+    Ok(sessions)
+}
+
+/// One line describing a session, e.g. "paused evening session from 2024-03-14 (3 responses)"
+pub fn describe(session: &JournalSession) -> String {
+    let status = match session.metadata.status {
+        SessionStatus::Active => "unfinished",
+        SessionStatus::Paused => "paused",
+        SessionStatus::Completed => "completed",
+        SessionStatus::Abandoned => "abandoned",
+        SessionStatus::Archived => "archived",
+    };
+    let mode = match session.mode {
+        SessionMode::Morning => "morning",
+        SessionMode::Evening => "evening",
+    };
+    let started = session
+        .transcript
+        .first()
+        .map(|entry| {
+            entry
+                .timestamp
+                .with_timezone(&chrono::Local)
+                .format(" from %Y-%m-%d %H:%M")
+                .to_string()
+        })
+        .unwrap_or_default();
+    let responses = session
+        .transcript
+        .iter()
+        .filter(|entry| entry.speaker == Speaker::User)
+        .count();
+
+    format!(
+        "{status} {mode} session{started} ({responses} {})",
+        if responses == 1 {
+            "response"
+        } else {
+            "responses"
+        }
+    )
+}
+
+/// Write a session's metadata back to its doc, leaving the transcript as it is
+pub fn update_metadata(vault_path: &Path, session: &JournalSession) -> Result<()> {
+    let session_id = session
+        .metadata
+        .session_doc_id
+        .context("Session has not been saved yet")?;
+    pack::apply_checked(
+        vault_path,
+        Patch {
+            uuid: Some(session_id),
+            doc_type: None,
+            mode: PatchMode::MergeFrontmatter,
+            frontmatter: Some(json!({ "metadata": session.metadata })),
+            body: None,
+        },
+    )
+    .context("Failed to update session document")?;
+    Ok(())
+}
+
+/// The transcript as the session doc body. Archived sessions are only kept for reference, so
+/// they're stored compactly.
+pub fn transcript_body(session: &JournalSession) -> Result<String> {
+    let body = if session.metadata.status == SessionStatus::Archived {
+        serde_json::to_string(&session.transcript)
+    } else {
+        serde_json::to_string_pretty(&session.transcript)
+    };
+    body.context("Failed to serialize transcript")
+}
+
+/// Remove a session doc from the vault
+pub fn delete_session(vault_path: &Path, session_id: Uuid) -> Result<()> {
+    let path = vault_path.join("docs").join(format!("{session_id}.md"));
+    if path.exists() {
+        std::fs::remove_file(&path).context("Failed to delete session document")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aethel_core::apply_patch;
+    use tempfile::TempDir;
+
+    fn save(vault_path: &Path, session: &JournalSession) -> Uuid {
+        apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(SESSION_DOC_TYPE.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(json!({
+                    "mode": session.mode,
+                    "metadata": session.metadata,
+                    "session_type": "evening",
+                })),
+                body: Some(serde_json::to_string(&session.transcript).unwrap()),
+            },
+        )
+        .unwrap()
+        .uuid
+    }
+
+    #[test]
+    fn test_find_resumable_sessions() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+
+        let mut paused = JournalSession::new(SessionMode::Evening);
+        paused.add_entry(Speaker::User, "Halfway through".to_string());
+        paused.metadata.status = SessionStatus::Paused;
+        let paused_id = save(vault_path, &paused);
+
+        let interrupted_id = save(vault_path, &JournalSession::new(SessionMode::Morning));
+        let mut abandoned = JournalSession::new(SessionMode::Morning);
+        abandoned.metadata.status = SessionStatus::Abandoned;
+        save(vault_path, &abandoned);
+        let mut archived = JournalSession::new(SessionMode::Evening);
+        archived.archive(Uuid::new_v4());
+        save(vault_path, &archived);
+        assert_eq!(list_sessions(vault_path).unwrap().len(), 4);
+
+        let found = find_resumable(vault_path, None).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].metadata.session_doc_id, Some(paused_id));
+        assert!(describe(&found[0]).starts_with("paused evening session from "));
+        assert!(describe(&found[0]).ends_with("(1 response)"));
+
+        // An active session only counts if the index still points at it
+        let found = find_resumable(vault_path, Some(interrupted_id)).unwrap();
+        assert_eq!(found.len(), 2);
+
+        delete_session(vault_path, paused_id).unwrap();
+        assert!(load_session(vault_path, paused_id).is_err());
+    }
+
+    #[test]
+    fn test_sessions_without_status_are_not_resumable_once_completed() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+
+        // Sessions saved before `status` existed
+        let mut legacy_ids = Vec::new();
+        for metadata in [
+            json!({ "completed_at": "2024-03-14T21:00:00Z" }),
+            json!({ "final_entry_id": Uuid::new_v4() }),
+        ] {
+            let session_id = apply_patch(
+                vault_path,
+                Patch {
+                    uuid: None,
+                    doc_type: Some(SESSION_DOC_TYPE.to_string()),
+                    mode: PatchMode::Create,
+                    frontmatter: Some(json!({
+                        "mode": "Evening",
+                        "session_type": "evening",
+                        "metadata": metadata,
+                    })),
+                    body: Some(r#"[{"speaker":"User","content":"Done for today","timestamp":"2024-03-14T20:55:00Z"}]"#.to_string()),
+                },
+            )
+            .unwrap()
+            .uuid;
+            legacy_ids.push(session_id);
+        }
+
+        let completed = load_session(vault_path, legacy_ids[0]).unwrap();
+        assert_eq!(completed.metadata.status, SessionStatus::Completed);
+        assert!(describe(&completed).starts_with("completed evening session"));
+        let archived = load_session(vault_path, legacy_ids[1]).unwrap();
+        assert_eq!(archived.metadata.status, SessionStatus::Archived);
+
+        for session_id in legacy_ids {
+            assert!(find_resumable(vault_path, Some(session_id))
+                .unwrap()
+                .is_empty());
+        }
+    }
+}
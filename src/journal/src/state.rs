@@ -1,6 +1,7 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::path::PathBuf;
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -14,6 +15,12 @@ pub enum State {
         analysis: String,
     },
     Done(WriteResult),
+    /// Saved mid-session to be continued later; the index still points at the session
+    Paused(JournalSession),
+    /// Ended without writing an entry; the session doc is kept
+    Abandoned(JournalSession),
+    /// Ended and the session doc deleted
+    Discarded,
     Error(String),
 }
 
@@ -32,41 +39,173 @@ pub enum SessionMode {
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct TranscriptEntry {
+    /// Stays the same when the line is revised
+    #[serde(default = "Uuid::new_v4")]
+    pub id: Uuid,
     pub timestamp: DateTime<Utc>,
     pub speaker: Speaker,
     pub content: String,
+    /// Marked as meaningful by the user with `/star`
+    #[serde(default)]
+    pub starred: bool,
+    /// Earlier versions of `content`, oldest first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub revisions: Vec<Revision>,
 }
 
+/// An earlier version of a transcript line
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Revision {
+    pub content: String,
+    pub revised_at: DateTime<Utc>,
+}
+
+/// A line taken out of the transcript, kept so the session doc shows what changed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetractedEntry {
+    pub entry: TranscriptEntry,
+    pub retracted_at: DateTime<Utc>,
+    pub reason: RetractionReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RetractionReason {
+    /// Taken back by the user with `/undo`
+    Undone,
+    /// A coach reply replaced by a new one
+    Regenerated,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Speaker {
     User,
     Coach,
     System,
 }
 
+/// Where a session stands, recorded in its doc
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStatus {
+    /// In progress, or interrupted without being paused
+    #[default]
+    Active,
+    Paused,
+    /// Stopped and written up as an entry
+    Completed,
+    /// Ended without writing an entry
+    Abandoned,
+    /// Written up as an entry, which `final_entry_id` links to
+    Archived,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct SessionMetadata {
     pub session_doc_id: Option<Uuid>,
+    #[serde(default)]
+    pub status: SessionStatus,
     pub final_entry_id: Option<Uuid>,
     pub completed_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
+    #[serde(default)]
     pub custom_fields: HashMap<String, serde_json::Value>,
+    #[serde(default)]
+    pub transcript_summary: Option<TranscriptSummary>,
+    /// Private sessions never invoke an AI backend
+    #[serde(default)]
+    pub private: bool,
+    /// Past entries shared with the coach as context for this session
+    #[serde(default)]
+    pub resurfaced: Vec<ResurfacedEntry>,
+    /// Tags added during the session with `/tag`
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Lines undone or regenerated during the session, oldest first
+    #[serde(default)]
+    pub retracted: Vec<RetractedEntry>,
+}
+
+impl SessionMetadata {
+    /// Whether the session was finished. Sessions saved before `status` existed only record
+    /// this in `completed_at` and `final_entry_id`.
+    pub fn has_ended(&self) -> bool {
+        self.completed_at.is_some()
+            || self.final_entry_id.is_some()
+            || matches!(
+                self.status,
+                SessionStatus::Completed | SessionStatus::Abandoned | SessionStatus::Archived
+            )
+    }
+}
+
+/// A snippet of a past entry shown at session start ("one year ago today", ...)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResurfacedEntry {
+    pub entry_id: Uuid,
+    /// When it was written relative to today, e.g. "One week ago"
+    pub label: String,
+    pub date: chrono::NaiveDate,
+    pub snippet: String,
+}
+
+/// Rolling summary of older transcript entries, kept so long sessions fit the token budget
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranscriptSummary {
+    pub summary: String,
+    /// Number of leading transcript entries covered by `summary`
+    pub entries_covered: usize,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WriteResult {
     pub entry_id: Uuid,
-    pub entry_path: String,
+    /// Absolute path of the entry doc
+    pub entry_path: PathBuf,
     pub session_completed: bool,
+    /// Whether the entry holds an AI analysis, a queued one or a local summary
+    pub analysis_status: AnalysisStatus,
+    /// Tags the user might want to add with `journal tags add`
+    pub suggested_tags: Vec<String>,
+}
+
+/// Whether a journal entry's analysis section holds a finished AI analysis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnalysisStatus {
+    Complete,
+    /// The AI backend was unavailable; the analysis is queued for `journal analyze --pending`
+    Pending,
+    /// Private session summarized locally, never analyzed by an AI backend
+    Local,
 }
 
 impl State {
     pub fn is_terminal(&self) -> bool {
-        matches!(self, State::Done(_) | State::Error(_))
+        matches!(
+            self,
+            State::Done(_)
+                | State::Paused(_)
+                | State::Abandoned(_)
+                | State::Discarded
+                | State::Error(_)
+        )
     }
 
     pub fn is_interactive(&self) -> bool {
         matches!(self, State::PromptingForNew | State::InSession(_))
     }
+
+    /// The session being journaled, if the state carries one
+    pub fn session_mut(&mut self) -> Option<&mut JournalSession> {
+        match self {
+            State::InSession(session)
+            | State::Analyzing(session)
+            | State::AnalysisReady { session, .. } => Some(session),
+            _ => None,
+        }
+    }
 }
 
 impl SessionMode {
@@ -107,18 +246,28 @@ impl JournalSession {
             transcript: Vec::new(),
             metadata: SessionMetadata {
                 session_doc_id: None,
+                status: SessionStatus::Active,
                 final_entry_id: None,
                 completed_at: None,
+                archived_at: None,
                 custom_fields: HashMap::new(),
+                transcript_summary: None,
+                private: false,
+                resurfaced: Vec::new(),
+                tags: Vec::new(),
+                retracted: Vec::new(),
             },
         }
     }
 
     pub fn add_entry(&mut self, speaker: Speaker, content: String) {
         self.transcript.push(TranscriptEntry {
+            id: Uuid::new_v4(),
             timestamp: Utc::now(),
             speaker,
             content,
+            starred: false,
+            revisions: Vec::new(),
         });
     }
 
@@ -150,7 +299,101 @@ impl JournalSession {
         summary
     }
 
+    /// Next question from the mode's built-in list that hasn't been asked yet, used
+    /// instead of the AI coach in private sessions
+    pub fn next_offline_question(&self) -> Option<&'static str> {
+        self.mode
+            .get_initial_questions()
+            .into_iter()
+            .find(|question| {
+                !self.transcript.iter().any(|entry| {
+                    matches!(entry.speaker, Speaker::Coach) && entry.content == *question
+                })
+            })
+    }
+
+    fn last_response_index(&self) -> Option<usize> {
+        self.transcript
+            .iter()
+            .rposition(|entry| matches!(entry.speaker, Speaker::User))
+    }
+
+    /// Move the transcript from `index` on into `metadata.retracted`
+    fn retract_from(&mut self, index: usize, reason: RetractionReason) {
+        let retracted_at = Utc::now();
+        let removed: Vec<_> = self.transcript.drain(index..).collect();
+        self.metadata
+            .retracted
+            .extend(removed.into_iter().map(|entry| RetractedEntry {
+                entry,
+                retracted_at,
+                reason,
+            }));
+
+        // The rolling summary can't cover lines that are gone
+        if let Some(summary) = &mut self.metadata.transcript_summary {
+            summary.entries_covered = summary.entries_covered.min(index);
+        }
+    }
+
+    /// Take back the last user response and anything said after it, returning the response
+    pub fn retract_last_response(&mut self) -> Option<String> {
+        let index = self.last_response_index()?;
+        let content = self.transcript[index].content.clone();
+        self.retract_from(index, RetractionReason::Undone);
+        Some(content)
+    }
+
+    /// Set aside everything said after the last user response so the coach can reply again,
+    /// returning the response
+    pub fn regenerate_reply(&mut self) -> Option<String> {
+        let index = self.last_response_index()?;
+        self.retract_from(index + 1, RetractionReason::Regenerated);
+        Some(self.transcript[index].content.clone())
+    }
+
+    /// Replace the last user response with `content`, keeping the old version in its
+    /// revisions. Replies to the old version are set aside to be regenerated.
+    pub fn revise_last_response(&mut self, content: String) -> bool {
+        let Some(index) = self.last_response_index() else {
+            return false;
+        };
+        self.retract_from(index + 1, RetractionReason::Regenerated);
+
+        let entry = &mut self.transcript[index];
+        let previous = std::mem::replace(&mut entry.content, content);
+        entry.revisions.push(Revision {
+            content: previous,
+            revised_at: Utc::now(),
+        });
+        true
+    }
+
+    /// Star the latest line said by `speaker`, returning whether there was one
+    pub fn star_latest(&mut self, speaker: &Speaker) -> bool {
+        match self
+            .transcript
+            .iter_mut()
+            .rev()
+            .find(|entry| entry.speaker == *speaker)
+        {
+            Some(entry) => {
+                entry.starred = true;
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn mark_completed(&mut self) {
+        self.metadata.status = SessionStatus::Completed;
         self.metadata.completed_at = Some(Utc::now());
     }
+
+    /// Link the session to the entry written from it and archive it
+    pub fn archive(&mut self, entry_id: Uuid) {
+        self.metadata.status = SessionStatus::Archived;
+        self.metadata.final_entry_id = Some(entry_id);
+        self.metadata.archived_at = Some(Utc::now());
+    }
 }
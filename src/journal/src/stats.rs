@@ -0,0 +1,293 @@
+use crate::entries;
+use crate::state::JournalSession;
+use crate::streak::Streak;
+use aethel_core::Doc;
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Widest sparkline drawn in the terminal; longer series are averaged into buckets
+const SPARKLINE_WIDTH: usize = 40;
+
+/// Widest bar in the session count chart
+const BAR_WIDTH: usize = 30;
+
+/// Number of themes listed in the dashboard
+const THEME_COUNT: usize = 5;
+
+const SPARK_CHARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// Statistics computed locally from a session transcript
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionStats {
+    pub responses: usize,
+    pub words: usize,
+    pub duration: Option<Duration>,
+}
+
+impl SessionStats {
+    pub fn from_session(session: &JournalSession) -> Self {
+        let responses = session.get_user_responses();
+        let words = responses
+            .iter()
+            .map(|entry| entry.content.split_whitespace().count())
+            .sum();
+        let duration = match (session.transcript.first(), session.transcript.last()) {
+            (Some(first), Some(last)) => Some(last.timestamp - first.timestamp),
+            _ => None,
+        };
+
+        Self {
+            responses: responses.len(),
+            words,
+            duration,
+        }
+    }
+
+    pub fn average_words(&self) -> usize {
+        self.words.checked_div(self.responses).unwrap_or(0)
+    }
+}
+
+/// Summary shown and stored in place of an AI analysis for private sessions
+pub fn local_summary(session: &JournalSession) -> String {
+    let stats = SessionStats::from_session(session);
+    let duration = stats
+        .duration
+        .map(|d| match d.num_minutes() {
+            0 => "under a minute".to_string(),
+            minutes => format!("{minutes} min"),
+        })
+        .unwrap_or_else(|| "-".to_string());
+
+    format!(
+        "**Session Summary (local only)**\n\n\
+        - Responses: {}\n\
+        - Words written: {}\n\
+        - Average words per response: {}\n\
+        - Duration: {}\n\n\
+        This was a private session. Nothing was sent to an AI backend.",
+        stats.responses,
+        stats.words,
+        stats.average_words(),
+        duration
+    )
+}
+
+/// A mood or energy score from one entry
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScorePoint {
+    pub date: NaiveDate,
+    pub score: f64,
+}
+
+/// Trends across all entries in a date window, for `journal stats`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrendStats {
+    pub since: Option<NaiveDate>,
+    pub until: Option<NaiveDate>,
+    pub sessions: usize,
+    pub sessions_by_mode: BTreeMap<String, usize>,
+    pub mood: Vec<ScorePoint>,
+    pub energy: Vec<ScorePoint>,
+    pub current_streak: usize,
+    pub longest_streak: usize,
+    pub average_session_minutes: Option<f64>,
+    pub average_words: Option<f64>,
+    pub top_themes: Vec<String>,
+}
+
+impl TrendStats {
+    /// Compute trends from `entries` (oldest first), reading each entry's linked session for
+    /// its timing
+    pub fn from_entries(
+        vault_path: &Path,
+        entries: &[Doc],
+        since: Option<NaiveDate>,
+        until: Option<NaiveDate>,
+        today: NaiveDate,
+        grace_days: u32,
+    ) -> Self {
+        let mut sessions_by_mode = BTreeMap::new();
+        for entry in entries {
+            let mode = entry
+                .frontmatter_extra
+                .get("mode")
+                .and_then(|mode| mode.as_str())
+                .unwrap_or("Unknown");
+            *sessions_by_mode.entry(mode.to_string()).or_insert(0) += 1;
+        }
+
+        let points = |score: fn(&Doc) -> Option<f64>| {
+            entries
+                .iter()
+                .filter_map(|entry| {
+                    Some(ScorePoint {
+                        date: entries::entry_date(entry),
+                        score: score(entry)?,
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let session_minutes = entries.iter().filter_map(|entry| {
+            let transcript = entries::linked_transcript(vault_path, entry)?;
+            let duration = transcript.last()?.timestamp - transcript.first()?.timestamp;
+            Some(duration.num_seconds() as f64 / 60.0)
+        });
+        let words = entries.iter().map(|entry| {
+            entries::user_lines(&entry.body)
+                .map(|line| line.split_whitespace().count())
+                .sum::<usize>() as f64
+        });
+
+        let dates: Vec<_> = entries.iter().map(entries::entry_date).collect();
+        let streak = Streak::compute(&dates, until.unwrap_or(today), grace_days);
+
+        Self {
+            since,
+            until,
+            sessions: entries.len(),
+            sessions_by_mode,
+            mood: points(entries::mood_score),
+            energy: points(entries::energy_score),
+            current_streak: streak.current,
+            longest_streak: streak.longest,
+            average_session_minutes: crate::digest::average(session_minutes),
+            average_words: crate::digest::average(words),
+            top_themes: entries::top_themes(entries, THEME_COUNT),
+        }
+    }
+
+    /// Charts and figures for the terminal
+    pub fn render(&self) -> String {
+        let window = match (self.since, self.until) {
+            (None, None) => "all time".to_string(),
+            (since, until) => format!(
+                "{} to {}",
+                since.map_or("the beginning".to_string(), |d| d.to_string()),
+                until.map_or("today".to_string(), |d| d.to_string())
+            ),
+        };
+        let mut output = format!("📊 Journal statistics ({window})\n\n");
+        output.push_str(&format!("{:<16}{}\n", "Sessions", self.sessions));
+
+        let most = self.sessions_by_mode.values().copied().max().unwrap_or(0);
+        for (mode, count) in &self.sessions_by_mode {
+            output.push_str(&format!(
+                "  {:<14}{} {count}\n",
+                mode,
+                bar(*count, most, BAR_WIDTH)
+            ));
+        }
+
+        for (label, points) in [("Mood", &self.mood), ("Energy", &self.energy)] {
+            let scores: Vec<f64> = points.iter().map(|point| point.score).collect();
+            let line = match crate::digest::average(scores.iter().copied()) {
+                Some(average) => format!(
+                    "{}  avg {average:.1} / 3",
+                    sparkline(&scores, 1.0, 3.0, SPARKLINE_WIDTH)
+                ),
+                None => "-".to_string(),
+            };
+            output.push_str(&format!("{label:<16}{line}\n"));
+        }
+
+        output.push_str(&format!(
+            "{:<16}{} days (longest {})\n",
+            "Streak", self.current_streak, self.longest_streak
+        ));
+        output.push_str(&format!(
+            "{:<16}{}\n",
+            "Avg. length",
+            self.average_session_minutes
+                .map_or("-".to_string(), |minutes| format!("{minutes:.0} min"))
+        ));
+        output.push_str(&format!(
+            "{:<16}{}\n",
+            "Avg. words",
+            self.average_words
+                .map_or("-".to_string(), |words| format!("{words:.0} per session"))
+        ));
+        let themes = if self.top_themes.is_empty() {
+            "-".to_string()
+        } else {
+            self.top_themes.join(", ")
+        };
+        output.push_str(&format!("{:<16}{themes}", "Top themes"));
+
+        output
+    }
+}
+
+/// Unicode sparkline of `values` between `min` and `max`, averaged down to at most `width`
+pub fn sparkline(values: &[f64], min: f64, max: f64, width: usize) -> String {
+    if values.is_empty() || width == 0 {
+        return String::new();
+    }
+
+    let bucket_size = values.len().div_ceil(width);
+    values
+        .chunks(bucket_size)
+        .map(|bucket| {
+            let value = bucket.iter().sum::<f64>() / bucket.len() as f64;
+            let fraction = ((value - min) / (max - min)).clamp(0.0, 1.0);
+            SPARK_CHARS[(fraction * (SPARK_CHARS.len() - 1) as f64).round() as usize]
+        })
+        .collect()
+}
+
+/// Horizontal bar for `count` scaled so `most` fills `width`
+fn bar(count: usize, most: usize, width: usize) -> String {
+    let filled = (count * width).checked_div(most).unwrap_or(0);
+    format!("{:<width$}", "█".repeat(filled), width = width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::{SessionMode, Speaker};
+
+    #[test]
+    fn test_session_stats() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(
+            Speaker::System,
+            "Starting evening journal session".to_string(),
+        );
+        session.add_entry(Speaker::User, "It was a long day".to_string());
+        session.add_entry(Speaker::Coach, "What went well today?".to_string());
+        session.add_entry(Speaker::User, "Lunch with friends".to_string());
+
+        let stats = SessionStats::from_session(&session);
+        assert_eq!(stats.responses, 2);
+        assert_eq!(stats.words, 8);
+        assert_eq!(stats.average_words(), 4);
+        assert!(stats.duration.is_some());
+    }
+
+    #[test]
+    fn test_empty_session_stats() {
+        let session = JournalSession::new(SessionMode::Morning);
+        let stats = SessionStats::from_session(&session);
+        assert_eq!(stats.responses, 0);
+        assert_eq!(stats.average_words(), 0);
+        assert_eq!(stats.duration, None);
+        assert!(local_summary(&session).contains("Duration: -"));
+    }
+
+    #[test]
+    fn test_sparkline() {
+        assert_eq!(sparkline(&[1.0, 2.0, 3.0], 1.0, 3.0, 10), "▁▅█");
+        // Long series are averaged into buckets
+        assert_eq!(sparkline(&[1.0, 1.0, 3.0, 3.0], 1.0, 3.0, 2), "▁█");
+        assert_eq!(sparkline(&[], 1.0, 3.0, 10), "");
+    }
+
+    #[test]
+    fn test_bar() {
+        assert_eq!(bar(5, 10, 4), "██  ");
+        assert_eq!(bar(0, 0, 2), "  ");
+    }
+}
@@ -0,0 +1,300 @@
+use crate::config::StreakConfig;
+use crate::entries::{self, EntryFilter};
+use crate::state::SessionMode;
+use aethel_core::Doc;
+use anyhow::Result;
+use chrono::{Datelike, Duration, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::Path;
+
+/// A journaling goal such as "evening 5x/week", stored in config in that form
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Goal {
+    /// `None` counts sessions of any mode
+    pub mode: Option<SessionMode>,
+    pub times_per_week: u32,
+}
+
+impl TryFrom<String> for Goal {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || format!("Invalid goal '{value}', expected e.g. \"evening 5x/week\"");
+        let (mode, frequency) = value.trim().split_once(' ').ok_or_else(invalid)?;
+
+        let mode = match mode.to_lowercase().as_str() {
+            "morning" => Some(SessionMode::Morning),
+            "evening" => Some(SessionMode::Evening),
+            "any" => None,
+            _ => return Err(invalid()),
+        };
+        let times_per_week = frequency
+            .trim()
+            .strip_suffix("x/week")
+            .and_then(|times| times.parse().ok())
+            .filter(|times| (1..=14).contains(times))
+            .ok_or_else(invalid)?;
+
+        Ok(Self {
+            mode,
+            times_per_week,
+        })
+    }
+}
+
+impl From<Goal> for String {
+    fn from(goal: Goal) -> Self {
+        goal.to_string()
+    }
+}
+
+impl fmt::Display for Goal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.mode {
+            Some(SessionMode::Morning) => "morning",
+            Some(SessionMode::Evening) => "evening",
+            None => "any",
+        };
+        write!(f, "{mode} {}x/week", self.times_per_week)
+    }
+}
+
+/// Current and longest runs of journaling days
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct Streak {
+    pub current: usize,
+    pub longest: usize,
+}
+
+impl Streak {
+    /// Count consecutive days with an entry. Up to `grace_days` missed days in a row don't
+    /// break a streak, though they don't add to it either. The current streak stays alive
+    /// until the day is over, so no entry yet today doesn't reset it.
+    pub fn compute(dates: &[NaiveDate], today: NaiveDate, grace_days: u32) -> Self {
+        let mut days: Vec<_> = dates
+            .iter()
+            .copied()
+            .filter(|date| *date <= today)
+            .collect();
+        days.sort();
+        days.dedup();
+
+        let allowed_gap = Duration::days(grace_days as i64 + 1);
+        let mut longest = 0;
+        let mut run = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for day in &days {
+            run = match previous {
+                Some(prev) if *day - prev <= allowed_gap => run + 1,
+                _ => 1,
+            };
+            longest = longest.max(run);
+            previous = Some(*day);
+        }
+
+        let current = match days.last() {
+            Some(last) if today - *last <= allowed_gap => run,
+            _ => 0,
+        };
+        Self { current, longest }
+    }
+}
+
+/// How a goal is going in the current week (Monday to Sunday)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GoalProgress {
+    pub goal: Goal,
+    pub done_this_week: u32,
+}
+
+impl GoalProgress {
+    pub fn is_met(&self) -> bool {
+        self.done_this_week >= self.goal.times_per_week
+    }
+}
+
+/// Streaks per mode and goal progress, shown when starting and finishing a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct StreakStatus {
+    pub overall: Streak,
+    pub morning: Streak,
+    pub evening: Streak,
+    pub goals: Vec<GoalProgress>,
+}
+
+fn days(count: usize) -> String {
+    match count {
+        1 => "1 day".to_string(),
+        n => format!("{n} days"),
+    }
+}
+
+fn entry_mode(entry: &Doc) -> Option<SessionMode> {
+    serde_json::from_value(entry.frontmatter_extra.get("mode")?.clone()).ok()
+}
+
+impl StreakStatus {
+    pub fn from_entries(entries: &[Doc], config: &StreakConfig, today: NaiveDate) -> Self {
+        let dates_for = |mode: Option<SessionMode>| {
+            entries
+                .iter()
+                .filter(|entry| mode.is_none() || entry_mode(entry) == mode)
+                .map(entries::entry_date)
+                .collect::<Vec<_>>()
+        };
+        let streak_for = |mode| Streak::compute(&dates_for(mode), today, config.grace_days);
+
+        let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let goals = config
+            .goals
+            .iter()
+            .map(|goal| GoalProgress {
+                goal: *goal,
+                done_this_week: dates_for(goal.mode)
+                    .into_iter()
+                    .filter(|date| (week_start..=today).contains(date))
+                    .count() as u32,
+            })
+            .collect();
+
+        Self {
+            overall: streak_for(None),
+            morning: streak_for(Some(SessionMode::Morning)),
+            evening: streak_for(Some(SessionMode::Evening)),
+            goals,
+        }
+    }
+
+    pub fn load(vault_path: &Path, config: &StreakConfig, today: NaiveDate) -> Result<Self> {
+        let entries = entries::find_entries(vault_path, &EntryFilter::default())?;
+        Ok(Self::from_entries(&entries, config, today))
+    }
+
+    /// One-line status, or `None` before the first entry
+    pub fn summary(&self) -> Option<String> {
+        if self.overall.longest == 0 {
+            return None;
+        }
+
+        let mut summary = match self.overall.current {
+            0 => format!("Longest streak: {}", days(self.overall.longest)),
+            1 => "🔥 1-day streak".to_string(),
+            days => format!(
+                "🔥 {days}-day streak (morning {}, evening {})",
+                self.morning.current, self.evening.current
+            ),
+        };
+        for progress in &self.goals {
+            let mark = if progress.is_met() { " ✓" } else { "" };
+            summary.push_str(&format!(
+                " · {}: {}/{} this week{mark}",
+                progress.goal, progress.done_this_week, progress.goal.times_per_week
+            ));
+        }
+        Some(summary)
+    }
+
+    /// Full breakdown for `journal streak`
+    pub fn details(&self, grace_days: u32) -> String {
+        let mut output = String::from("🔥 Journaling streaks\n\n");
+        for (label, streak) in [
+            ("All sessions", self.overall),
+            ("Morning", self.morning),
+            ("Evening", self.evening),
+        ] {
+            output.push_str(&format!(
+                "{label:<14}{} (longest {})\n",
+                days(streak.current),
+                streak.longest
+            ));
+        }
+
+        output.push_str(&match grace_days {
+            0 => "\nEvery day counts: missing one ends a streak.\n".to_string(),
+            1 => "\nGrace: 1 missed day in a row keeps a streak alive.\n".to_string(),
+            n => format!("\nGrace: up to {n} missed days in a row keep a streak alive.\n"),
+        });
+
+        if self.goals.is_empty() {
+            output.push_str("\nNo goals set. Add e.g. \"evening 5x/week\" to your config.");
+        } else {
+            output.push_str("\nGoals this week\n");
+            for progress in &self.goals {
+                let status = if progress.is_met() { "✓ met" } else { "" };
+                output.push_str(&format!(
+                    "  {:<18}{}/{} {status}\n",
+                    progress.goal.to_string(),
+                    progress.done_this_week,
+                    progress.goal.times_per_week
+                ));
+            }
+        }
+
+        output.trim_end().to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, day).unwrap()
+    }
+
+    #[test]
+    fn test_goal_parsing() {
+        let goal = Goal::try_from("evening 5x/week".to_string()).unwrap();
+        assert_eq!(goal.mode, Some(SessionMode::Evening));
+        assert_eq!(goal.times_per_week, 5);
+        assert_eq!(goal.to_string(), "evening 5x/week");
+
+        let any: Goal = serde_json::from_str("\"Any 3x/week\"").unwrap();
+        assert_eq!(any.mode, None);
+
+        assert!(Goal::try_from("evening daily".to_string()).is_err());
+        assert!(Goal::try_from("lunch 2x/week".to_string()).is_err());
+        assert!(Goal::try_from("evening 0x/week".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_streak_compute() {
+        let dates = vec![
+            date(1),
+            date(2),
+            date(3),
+            date(7),
+            date(9),
+            date(10),
+            date(10),
+        ];
+        assert_eq!(
+            Streak::compute(&dates, date(10), 0),
+            Streak {
+                current: 2,
+                longest: 3
+            }
+        );
+        // No entry yet today still continues yesterday's streak
+        assert_eq!(Streak::compute(&dates, date(11), 0).current, 2);
+        assert_eq!(Streak::compute(&dates, date(12), 0).current, 0);
+        assert_eq!(Streak::compute(&[], date(12), 0), Streak::default());
+    }
+
+    #[test]
+    fn test_streak_grace_days() {
+        let dates = vec![date(1), date(2), date(4), date(5), date(7)];
+        assert_eq!(
+            Streak::compute(&dates, date(7), 1),
+            Streak {
+                current: 5,
+                longest: 5
+            }
+        );
+        assert_eq!(Streak::compute(&dates, date(9), 1).current, 5);
+        assert_eq!(Streak::compute(&dates, date(10), 1).current, 0);
+        assert_eq!(Streak::compute(&dates, date(7), 0).longest, 2);
+    }
+}
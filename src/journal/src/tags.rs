@@ -0,0 +1,270 @@
+use crate::entries::{self, EntryFilter};
+use crate::pack;
+use crate::state::JournalSession;
+use aethel_core::{Patch, PatchMode};
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use uuid::Uuid;
+
+/// Most tags suggested for one entry
+const MAX_SUGGESTIONS: usize = 5;
+
+/// A tag and how many entries use it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagCount {
+    pub tag: String,
+    pub entries: usize,
+}
+
+/// Tidy a tag as it was typed: lowercase, without a leading `#`
+pub fn normalize(tag: &str) -> Option<String> {
+    let tag = tag.trim().trim_start_matches('#').to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Every tag used on an entry, most used first
+pub fn vocabulary(vault_path: &Path) -> Result<Vec<TagCount>> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for entry in entries::find_entries(vault_path, &EntryFilter::default())? {
+        for tag in entry.tags {
+            *counts.entry(tag).or_default() += 1;
+        }
+    }
+
+    let mut vocabulary: Vec<TagCount> = counts
+        .into_iter()
+        .map(|(tag, entries)| TagCount { tag, entries })
+        .collect();
+    vocabulary.sort_by(|a, b| b.entries.cmp(&a.entries).then_with(|| a.tag.cmp(&b.tag)));
+    Ok(vocabulary)
+}
+
+/// Tags to suggest for a finished session: tags already in use that the session mentions, then
+/// themes of the analysis that the user also wrote about. Tags the session has are left out.
+pub fn suggest(session: &JournalSession, analysis: &str, vocabulary: &[TagCount]) -> Vec<String> {
+    let user_lines: Vec<&str> = session
+        .get_user_responses()
+        .into_iter()
+        .map(|entry| entry.content.as_str())
+        .collect();
+    let user_words = words(user_lines.iter().copied());
+    let mentioned = words(user_lines.iter().copied().chain(analysis.lines()));
+
+    let known = vocabulary
+        .iter()
+        .map(|count| count.tag.clone())
+        .filter(|tag| mentioned.contains(tag));
+    let themes = entries::themes(
+        user_lines.iter().copied().chain(analysis.lines()),
+        usize::MAX,
+    )
+    .into_iter()
+    .filter(|theme| user_words.contains(theme));
+
+    let mut suggestions: Vec<String> = Vec::new();
+    for tag in known.chain(themes) {
+        if !session.metadata.tags.contains(&tag) && !suggestions.contains(&tag) {
+            suggestions.push(tag);
+        }
+    }
+    suggestions.truncate(MAX_SUGGESTIONS);
+    suggestions
+}
+
+/// Add tags to one entry. Returns the entry's tags afterwards.
+pub fn add(vault_path: &Path, entry_id: Uuid, tags: &[String]) -> Result<Vec<String>> {
+    let entry = entries::read_entry(vault_path, entry_id)?;
+    let mut entry_tags = entry.tags;
+    for tag in tags {
+        if !entry_tags.contains(tag) {
+            entry_tags.push(tag.clone());
+        }
+    }
+    set_tags(vault_path, entry_id, &entry_tags)?;
+    Ok(entry_tags)
+}
+
+/// Remove tags from one entry. Returns the entry's tags afterwards.
+pub fn remove(vault_path: &Path, entry_id: Uuid, tags: &[String]) -> Result<Vec<String>> {
+    let entry = entries::read_entry(vault_path, entry_id)?;
+    let mut entry_tags = entry.tags;
+    entry_tags.retain(|tag| !tags.contains(tag));
+    set_tags(vault_path, entry_id, &entry_tags)?;
+    Ok(entry_tags)
+}
+
+/// Rename a tag on every entry. Returns how many entries changed.
+pub fn rename(vault_path: &Path, from: &str, to: &str) -> Result<usize> {
+    let vocabulary = vocabulary(vault_path)?;
+    if !vocabulary.iter().any(|count| count.tag == from) {
+        anyhow::bail!("No entry is tagged '{from}'");
+    }
+    if vocabulary.iter().any(|count| count.tag == to) {
+        anyhow::bail!("'{to}' is already a tag; use `journal tags merge {from} --into {to}`");
+    }
+    merge(vault_path, &[from.to_string()], to)
+}
+
+/// Replace each of `from` with `into` on every entry. Returns how many entries changed.
+pub fn merge(vault_path: &Path, from: &[String], into: &str) -> Result<usize> {
+    let mut changed = 0;
+    for entry in entries::find_entries(vault_path, &EntryFilter::default())? {
+        let tags = entry.tags;
+        if !tags.iter().any(|tag| from.contains(tag)) {
+            continue;
+        }
+
+        let mut merged: Vec<String> = Vec::new();
+        for tag in tags {
+            let tag = if from.contains(&tag) {
+                into.to_string()
+            } else {
+                tag
+            };
+            if !merged.contains(&tag) {
+                merged.push(tag);
+            }
+        }
+        set_tags(vault_path, entry.uuid, &merged)?;
+        changed += 1;
+    }
+    Ok(changed)
+}
+
+fn set_tags(vault_path: &Path, entry_id: Uuid, tags: &[String]) -> Result<()> {
+    pack::apply_checked(
+        vault_path,
+        Patch {
+            uuid: Some(entry_id),
+            doc_type: None,
+            mode: PatchMode::MergeFrontmatter,
+            frontmatter: Some(json!({ "tags": tags })),
+            body: None,
+        },
+    )
+    .with_context(|| format!("Failed to update the tags of {entry_id}"))?;
+    Ok(())
+}
+
+/// The lowercase words in `lines`, keeping hyphenated words like tags whole
+fn words<'a>(lines: impl IntoIterator<Item = &'a str>) -> HashSet<String> {
+    lines
+        .into_iter()
+        .flat_map(|line| line.split(|c: char| !c.is_alphanumeric() && c != '-' && c != '\''))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entries::ENTRY_DOC_TYPE;
+    use crate::state::{SessionMode, Speaker};
+    use aethel_core::{apply_patch, read_doc};
+    use tempfile::TempDir;
+
+    fn create_entry(vault_path: &Path, tags: &[&str]) -> Uuid {
+        apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(ENTRY_DOC_TYPE.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(json!({
+                    "session_id": null,
+                    "mode": "Evening",
+                    "session_type": "evening",
+                    "title": "Evening Journal Entry",
+                    "tags": tags,
+                })),
+                body: Some("# Entry".to_string()),
+            },
+        )
+        .unwrap()
+        .uuid
+    }
+
+    fn tags_of(vault_path: &Path, entry_id: Uuid) -> Vec<String> {
+        read_doc(vault_path, &entry_id).unwrap().tags
+    }
+
+    #[test]
+    fn test_vocabulary_rename_and_merge() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+        let first = create_entry(vault_path, &["work", "running"]);
+        let second = create_entry(vault_path, &["work", "jogging"]);
+
+        let vocabulary = vocabulary(vault_path).unwrap();
+        assert_eq!(
+            vocabulary[0],
+            TagCount {
+                tag: "work".to_string(),
+                entries: 2
+            }
+        );
+        assert_eq!(vocabulary.len(), 3);
+
+        assert_eq!(rename(vault_path, "work", "career").unwrap(), 2);
+        assert_eq!(tags_of(vault_path, first), vec!["career", "running"]);
+        assert!(rename(vault_path, "running", "jogging").is_err());
+        assert!(rename(vault_path, "unused", "other").is_err());
+
+        assert_eq!(
+            merge(
+                vault_path,
+                &["running".to_string(), "jogging".to_string()],
+                "exercise"
+            )
+            .unwrap(),
+            2
+        );
+        assert_eq!(tags_of(vault_path, second), vec!["career", "exercise"]);
+
+        add(
+            vault_path,
+            first,
+            &["exercise".to_string(), "health".to_string()],
+        )
+        .unwrap();
+        assert_eq!(
+            tags_of(vault_path, first),
+            vec!["career", "exercise", "health"]
+        );
+        remove(vault_path, first, &["career".to_string()]).unwrap();
+        assert_eq!(tags_of(vault_path, first), vec!["exercise", "health"]);
+    }
+
+    #[test]
+    fn test_suggest_prefers_known_tags_then_shared_themes() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(
+            Speaker::User,
+            "Long day at work, but the garden kept me sane. The garden is thriving.".to_string(),
+        );
+        session.metadata.tags = vec!["work".to_string()];
+        let vocabulary = vec![
+            TagCount {
+                tag: "work".to_string(),
+                entries: 4,
+            },
+            TagCount {
+                tag: "sleep".to_string(),
+                entries: 3,
+            },
+            TagCount {
+                tag: "self-care".to_string(),
+                entries: 1,
+            },
+        ];
+        let analysis = "Tending the garden looks like self-care for you. Insights: insights.";
+
+        assert_eq!(
+            suggest(&session, analysis, &vocabulary),
+            vec!["self-care", "garden"]
+        );
+    }
+}
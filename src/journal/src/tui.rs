@@ -0,0 +1,461 @@
+use crate::action::{Action, InputContext, UserInput};
+use crate::state::{JournalSession, ResurfacedEntry, SessionMode, Speaker, State};
+use crate::streak::StreakStatus;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::terminal::{self, ClearType};
+use crossterm::{cursor, execute, queue};
+use std::io::{self, Stdout, Write};
+use std::time::Duration;
+
+/// Most rows the input box grows to before it scrolls
+const MAX_INPUT_ROWS: usize = 6;
+
+/// How often the screen refreshes while waiting for a key, to keep the elapsed time current
+const TICK: Duration = Duration::from_secs(1);
+
+const KEY_HINTS: &str =
+    "Enter send · Alt+Enter newline · /edit editor · ^S stop · ^P pause · ^Z undo";
+
+/// Whether the session's latest changes have been written to the vault
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveState {
+    NotSaved,
+    Saved,
+    Failed,
+}
+
+/// What the user is typing and how far the transcript is scrolled
+#[derive(Debug, Default)]
+struct Composer {
+    input: String,
+    /// Lines scrolled up from the bottom of the transcript
+    scroll: usize,
+}
+
+/// Full-screen interface: a scrollable transcript pane, an input box and a status bar.
+/// The terminal is restored when it is dropped.
+pub struct Tui {
+    stdout: Stdout,
+    composer: Composer,
+    /// Messages shown above the transcript, such as streaks and past entries
+    notices: Vec<String>,
+    backend: String,
+    pub save_state: SaveState,
+    /// A message shown above the input box until the next key press
+    flash: Option<String>,
+}
+
+impl Tui {
+    pub fn enter(backend: &str) -> Result<Self> {
+        let mut tui = Self {
+            stdout: io::stdout(),
+            composer: Composer::default(),
+            notices: Vec::new(),
+            backend: backend.to_string(),
+            save_state: SaveState::NotSaved,
+            flash: None,
+        };
+        tui.resume()?;
+        Ok(tui)
+    }
+
+    /// Hand the terminal back temporarily, e.g. to run the user's editor
+    pub fn suspend(&mut self) -> Result<()> {
+        execute!(self.stdout, terminal::LeaveAlternateScreen, cursor::Show)
+            .context("Failed to leave full-screen mode")?;
+        terminal::disable_raw_mode().context("Failed to restore the terminal")
+    }
+
+    pub fn resume(&mut self) -> Result<()> {
+        terminal::enable_raw_mode().context("Failed to enable raw terminal mode")?;
+        execute!(self.stdout, terminal::EnterAlternateScreen)
+            .context("Failed to enter full-screen mode")
+    }
+
+    pub fn flash(&mut self, message: String) {
+        self.flash = Some(message);
+    }
+
+    fn notice(&mut self, text: String) {
+        self.notices.push(text);
+    }
+
+    pub fn notice_streaks(&mut self, status: &StreakStatus) {
+        if let Some(summary) = status.summary() {
+            self.notice(summary);
+        }
+    }
+
+    pub fn show_resurfaced(&mut self, resurfaced: &[ResurfacedEntry]) {
+        if resurfaced.is_empty() {
+            return;
+        }
+
+        self.notice("On this day".to_string());
+        for entry in resurfaced {
+            self.notice(format!(
+                "  • {} ({}): {}",
+                entry.label, entry.date, entry.snippet
+            ));
+        }
+    }
+
+    /// Wait for the user to send something or press a key binding. Returns `None` if they
+    /// quit before a session started.
+    pub fn next_action(&mut self, state: &State) -> Result<Option<Action>> {
+        loop {
+            self.draw(state)?;
+            if !event::poll(TICK).context("Failed to read terminal events")? {
+                continue;
+            }
+
+            let Event::Key(key) = event::read().context("Failed to read terminal events")? else {
+                // Resizes and other events just need a redraw
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            self.flash = None;
+            if let Some(outcome) = self.composer.handle_key(key, state) {
+                return Ok(outcome);
+            }
+        }
+    }
+
+    pub fn draw(&mut self, state: &State) -> Result<()> {
+        let (width, height) = terminal::size().context("Failed to read terminal size")?;
+        let (width, height) = (width as usize, height as usize);
+
+        let mut input_lines = wrap(&self.composer.input, width.saturating_sub(2).max(1));
+        if input_lines.len() > MAX_INPUT_ROWS {
+            input_lines.drain(..input_lines.len() - MAX_INPUT_ROWS);
+        }
+        // Transcript, separator, input and status bar share the screen
+        let pane_height = height.saturating_sub(input_lines.len() + 2);
+
+        let lines = pane_lines(state, &self.notices, width);
+        self.composer.scroll = self
+            .composer
+            .scroll
+            .min(lines.len().saturating_sub(pane_height));
+        let end = lines.len() - self.composer.scroll;
+        let visible = &lines[end.saturating_sub(pane_height)..end];
+
+        queue!(
+            self.stdout,
+            terminal::Clear(ClearType::All),
+            cursor::MoveTo(0, 0)
+        )?;
+        for (row, line) in visible.iter().enumerate() {
+            queue!(self.stdout, cursor::MoveTo(0, row as u16), Print(line))?;
+        }
+
+        let separator = match &self.flash {
+            Some(message) => format!("── {message} "),
+            None if self.composer.scroll > 0 => {
+                format!("── ↑ {} more lines below ", self.composer.scroll)
+            }
+            None => String::new(),
+        };
+        queue!(
+            self.stdout,
+            cursor::MoveTo(0, pane_height as u16),
+            SetAttribute(Attribute::Dim),
+            Print(pad(&separator, width, '─')),
+            SetAttribute(Attribute::Reset)
+        )?;
+        for (row, line) in input_lines.iter().enumerate() {
+            queue!(
+                self.stdout,
+                cursor::MoveTo(0, (pane_height + 1 + row) as u16),
+                Print(format!("> {line}"))
+            )?;
+        }
+
+        let status = status_line(state, &self.backend, self.save_state, Utc::now());
+        queue!(
+            self.stdout,
+            cursor::MoveTo(0, height.saturating_sub(1) as u16),
+            SetAttribute(Attribute::Reverse),
+            Print(pad(&status, width, ' ')),
+            SetAttribute(Attribute::Reset)
+        )?;
+
+        let last_input = input_lines.last().map_or(0, |line| line.chars().count());
+        queue!(
+            self.stdout,
+            cursor::MoveTo(
+                (2 + last_input).min(width.saturating_sub(1)) as u16,
+                (pane_height + input_lines.len()) as u16
+            )
+        )?;
+        self.stdout.flush().context("Failed to draw the screen")
+    }
+}
+
+impl Composer {
+    /// Apply a key press, returning what the app should do next if it ends the wait
+    fn handle_key(&mut self, key: KeyEvent, state: &State) -> Option<Option<Action>> {
+        let in_session = matches!(state, State::InSession(_));
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Char('c') if ctrl => Some(in_session.then_some(Action::Pause)),
+            KeyCode::Char('s') if ctrl && in_session => Some(Some(Action::Stop)),
+            KeyCode::Char('p') if ctrl && in_session => Some(Some(Action::Pause)),
+            KeyCode::Char('z') if ctrl && in_session => {
+                // Put the retracted response back in the input box to be edited, unless
+                // that would throw away a draft
+                if let (State::InSession(session), true) = (state, self.input.is_empty()) {
+                    if let Some(last) = session.get_user_responses().last() {
+                        self.input = last.content.clone();
+                    }
+                }
+                self.scroll = 0;
+                Some(Some(Action::Undo))
+            }
+            KeyCode::Enter
+                if key
+                    .modifiers
+                    .intersects(KeyModifiers::ALT | KeyModifiers::SHIFT) =>
+            {
+                self.input.push('\n');
+                None
+            }
+            KeyCode::Enter => {
+                let context = if in_session {
+                    InputContext::InSession
+                } else {
+                    InputContext::ModeSelection
+                };
+                let input = std::mem::take(&mut self.input);
+                self.scroll = 0;
+                Some(Some(UserInput::new_with_context(input, context).processed))
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                None
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.input.push(c);
+                None
+            }
+            KeyCode::Up => {
+                self.scroll += 1;
+                None
+            }
+            KeyCode::Down => {
+                self.scroll = self.scroll.saturating_sub(1);
+                None
+            }
+            KeyCode::PageUp => {
+                self.scroll += 10;
+                None
+            }
+            KeyCode::PageDown => {
+                self.scroll = self.scroll.saturating_sub(10);
+                None
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Drop for Tui {
+    fn drop(&mut self) {
+        // Best effort: there is nothing more to do if the terminal can't be restored
+        let _ = execute!(self.stdout, terminal::LeaveAlternateScreen, cursor::Show);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// Word-wrap `text` to `width` columns, breaking words longer than a line
+pub fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.split('\n') {
+        let mut line = String::new();
+        for word in paragraph.split(' ') {
+            let mut word = word.to_string();
+            while word.chars().count() > width {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                let rest = word.split_off(word.char_indices().nth(width).map_or(0, |(i, _)| i));
+                lines.push(word);
+                word = rest;
+            }
+
+            let needed = line.chars().count() + word.chars().count() + 1;
+            if !line.is_empty() && needed > width {
+                lines.push(std::mem::take(&mut line));
+            } else if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(&word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn pad(text: &str, width: usize, fill: char) -> String {
+    let mut padded: String = text.chars().take(width).collect();
+    let len = padded.chars().count();
+    padded.extend(std::iter::repeat_n(fill, width - len));
+    padded
+}
+
+fn transcript_lines(session: &JournalSession, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for entry in &session.transcript {
+        let prefix = match entry.speaker {
+            Speaker::Coach => "Coach: ",
+            Speaker::User => "You: ",
+            Speaker::System => "* ",
+        };
+        lines.extend(wrap(&format!("{prefix}{}", entry.content), width));
+        lines.push(String::new());
+    }
+    lines
+}
+
+/// Everything the transcript pane would show, before scrolling
+pub fn pane_lines(state: &State, notices: &[String], width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = notices
+        .iter()
+        .flat_map(|notice| wrap(notice, width))
+        .collect();
+    if !lines.is_empty() {
+        lines.push(String::new());
+    }
+
+    match state {
+        State::PromptingForNew => {
+            lines.push("Welcome to your journal!".to_string());
+            lines.push("What kind of session would you like to start?".to_string());
+            lines.push("  (m)orning - Start your day with intention".to_string());
+            lines.push("  (e)vening - Reflect on your day".to_string());
+        }
+        State::InSession(session) | State::Paused(session) | State::Abandoned(session) => {
+            lines.extend(transcript_lines(session, width));
+        }
+        State::Analyzing(session) => {
+            lines.extend(transcript_lines(session, width));
+            lines.push("Analyzing your session...".to_string());
+        }
+        State::AnalysisReady { session, analysis } => {
+            lines.extend(transcript_lines(session, width));
+            lines.extend(wrap(analysis, width));
+        }
+        State::Initializing | State::Done(_) | State::Discarded | State::Error(_) => {}
+    }
+    lines
+}
+
+/// Mode, elapsed time, words written, save state and backend, followed by key hints
+pub fn status_line(
+    state: &State,
+    backend: &str,
+    save_state: SaveState,
+    now: DateTime<Utc>,
+) -> String {
+    let session = match state {
+        State::InSession(session) | State::Analyzing(session) | State::Paused(session) => session,
+        State::AnalysisReady { session, .. } => session,
+        _ => return format!(" journal │ {KEY_HINTS}"),
+    };
+
+    let mode = match session.mode {
+        SessionMode::Morning => "Morning",
+        SessionMode::Evening => "Evening",
+    };
+    let elapsed = session
+        .transcript
+        .first()
+        .map(|entry| (now - entry.timestamp).num_seconds().max(0))
+        .unwrap_or(0);
+    let words: usize = session
+        .get_user_responses()
+        .iter()
+        .map(|entry| entry.content.split_whitespace().count())
+        .sum();
+    let save = match save_state {
+        SaveState::NotSaved => "not saved",
+        SaveState::Saved => "saved",
+        SaveState::Failed => "save failed",
+    };
+    let backend = if session.metadata.private {
+        "private"
+    } else {
+        backend
+    };
+
+    format!(
+        " {mode} │ {}:{:02} │ {words} words │ {save} │ {backend} │ {KEY_HINTS}",
+        elapsed / 60,
+        elapsed % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(
+            wrap("the quick brown fox", 10),
+            vec!["the quick", "brown fox"]
+        );
+        assert_eq!(wrap("one\ntwo", 10), vec!["one", "two"]);
+        assert_eq!(wrap("abcdefghij klm", 4), vec!["abcd", "efgh", "ij", "klm"]);
+        assert_eq!(wrap("", 10), vec![""]);
+    }
+
+    #[test]
+    fn test_status_line() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::User, "A long and tiring day".to_string());
+        let started = session.transcript[0].timestamp;
+
+        let status = status_line(
+            &State::InSession(session.clone()),
+            "claude",
+            SaveState::Saved,
+            started + chrono::Duration::seconds(95),
+        );
+        assert!(status.starts_with(" Evening │ 1:35 │ 5 words │ saved │ claude │"));
+
+        session.metadata.private = true;
+        let status = status_line(
+            &State::InSession(session),
+            "claude",
+            SaveState::NotSaved,
+            started,
+        );
+        assert!(status.contains("│ not saved │ private │"));
+    }
+
+    #[test]
+    fn test_undo_key_restores_last_response() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::User, "First draft".to_string());
+        let state = State::InSession(session);
+
+        let mut composer = Composer::default();
+        let undo = KeyEvent::new(KeyCode::Char('z'), KeyModifiers::CONTROL);
+        assert_eq!(composer.handle_key(undo, &state), Some(Some(Action::Undo)));
+        assert_eq!(composer.input, "First draft");
+
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert_eq!(
+            composer.handle_key(enter, &state),
+            Some(Some(Action::UserResponse("First draft".to_string())))
+        );
+        assert!(composer.input.is_empty());
+    }
+}
@@ -1,7 +1,9 @@
 use crate::action::Action;
 use crate::effects::Effect;
-use crate::state::{JournalSession, SessionMode, Speaker, State, WriteResult};
-use uuid::Uuid;
+use crate::state::{
+    AnalysisStatus, JournalSession, SessionMode, SessionStatus, Speaker, State, WriteResult,
+};
+use crate::stats;
 
 pub fn update(state: State, action: Action) -> (State, Vec<Effect>) {
     match (state, action) {
@@ -20,13 +22,7 @@ pub fn update(state: State, action: Action) -> (State, Vec<Effect>) {
 
             session.add_entry(
                 Speaker::System,
-                format!(
-                    "Starting {} journal session",
-                    match mode {
-                        SessionMode::Morning => "morning",
-                        SessionMode::Evening => "evening",
-                    }
-                ),
+                format!("Starting {} journal session", mode_name(mode)),
             );
 
             // Note: The session document UUID will be created during SaveSession effect
@@ -37,6 +33,51 @@ pub fn update(state: State, action: Action) -> (State, Vec<Effect>) {
             )
         }
 
+        // Switching the session to local-only mode
+        (State::InSession(mut session), Action::MakePrivate) => {
+            if !session.metadata.private {
+                session.metadata.private = true;
+                session.add_entry(
+                    Speaker::System,
+                    "Private session - nothing will be sent to an AI backend".to_string(),
+                );
+            }
+
+            (
+                State::InSession(session.clone()),
+                vec![Effect::SaveSession(session)],
+            )
+        }
+
+        // Resurfaced entries are kept with the session so every coach prompt can include them.
+        // Private sessions never share anything with the coach.
+        (State::InSession(mut session), Action::ShareResurfaced(resurfaced)) => {
+            if session.metadata.private {
+                return (State::InSession(session), vec![]);
+            }
+            session.metadata.resurfaced = resurfaced;
+
+            (
+                State::InSession(session.clone()),
+                vec![Effect::SaveSession(session)],
+            )
+        }
+
+        // User responses in a private session get the next built-in question instead of the coach
+        (State::InSession(mut session), Action::UserResponse(response))
+            if session.metadata.private =>
+        {
+            session.add_entry(Speaker::User, response);
+            if let Some(question) = session.next_offline_question() {
+                session.add_entry(Speaker::Coach, question.to_string());
+            }
+
+            (
+                State::InSession(session.clone()),
+                vec![Effect::SaveSession(session)],
+            )
+        }
+
         // User responses during session
         (State::InSession(mut session), Action::UserResponse(response)) => {
             session.add_entry(Speaker::User, response.clone());
@@ -62,9 +103,224 @@ pub fn update(state: State, action: Action) -> (State, Vec<Effect>) {
             )
         }
 
+        // Older turns were folded into the rolling summary - persist it and retry the coach request
+        (State::InSession(mut session), Action::TranscriptSummarized(summary)) => {
+            session.metadata.transcript_summary = Some(summary);
+            let user_response = session
+                .get_user_responses()
+                .last()
+                .map(|entry| entry.content.clone())
+                .unwrap_or_default();
+
+            (
+                State::InSession(session.clone()),
+                vec![
+                    Effect::SaveSession(session.clone()),
+                    Effect::RequestCoachResponse {
+                        session,
+                        user_response,
+                    },
+                ],
+            )
+        }
+
+        // A newly created session doc reported its UUID - remember it so later saves update it
+        (mut state, Action::SessionSaved(session_id)) => {
+            if let Some(session) = state.session_mut() {
+                session.metadata.session_doc_id.get_or_insert(session_id);
+            }
+            (state, vec![])
+        }
+
+        // Moving to next question - private sessions skip ahead in the built-in questions
+        (State::InSession(mut session), Action::NextQuestion) if session.metadata.private => {
+            match session.next_offline_question() {
+                Some(question) => {
+                    session.add_entry(Speaker::Coach, question.to_string());
+                    (
+                        State::InSession(session.clone()),
+                        vec![Effect::SaveSession(session)],
+                    )
+                }
+                None => (State::InSession(session), vec![]),
+            }
+        }
+
         // Moving to next question
         (State::InSession(session), Action::NextQuestion) => (State::InSession(session), vec![]),
 
+        // Taking back the last response - the coach's reply to it goes too
+        (State::InSession(mut session), Action::Undo) => {
+            if session.retract_last_response().is_none() {
+                return (State::InSession(session), vec![]);
+            }
+
+            (
+                State::InSession(session.clone()),
+                vec![Effect::SaveSession(session)],
+            )
+        }
+
+        // Asking the coach again - private sessions have no coach to ask
+        (State::InSession(mut session), Action::Retry) if !session.metadata.private => {
+            let Some(user_response) = session.regenerate_reply() else {
+                return (State::InSession(session), vec![]);
+            };
+
+            (
+                State::InSession(session.clone()),
+                vec![
+                    Effect::SaveSession(session.clone()),
+                    Effect::RequestCoachResponse {
+                        session,
+                        user_response,
+                    },
+                ],
+            )
+        }
+
+        (State::InSession(session), Action::Retry) => (State::InSession(session), vec![]),
+
+        // Revising the last response - the old version is kept and the reply regenerated
+        (State::InSession(mut session), Action::ReviseLast(content)) => {
+            if !session.revise_last_response(content.clone()) {
+                return (State::InSession(session), vec![]);
+            }
+
+            if session.metadata.private {
+                if let Some(question) = session.next_offline_question() {
+                    session.add_entry(Speaker::Coach, question.to_string());
+                }
+                return (
+                    State::InSession(session.clone()),
+                    vec![Effect::SaveSession(session)],
+                );
+            }
+
+            (
+                State::InSession(session.clone()),
+                vec![
+                    Effect::SaveSession(session.clone()),
+                    Effect::RequestCoachResponse {
+                        session,
+                        user_response: content,
+                    },
+                ],
+            )
+        }
+
+        // Skipping ahead to a built-in question the session hasn't asked yet
+        (State::InSession(mut session), Action::Skip) => match session.next_offline_question() {
+            Some(question) => {
+                session.add_entry(Speaker::Coach, question.to_string());
+                (
+                    State::InSession(session.clone()),
+                    vec![Effect::SaveSession(session)],
+                )
+            }
+            None => (State::InSession(session), vec![]),
+        },
+
+        (State::InSession(mut session), Action::Tag(tags)) => {
+            for tag in tags {
+                if !session.metadata.tags.contains(&tag) {
+                    session.metadata.tags.push(tag);
+                }
+            }
+
+            (
+                State::InSession(session.clone()),
+                vec![Effect::SaveSession(session)],
+            )
+        }
+
+        (State::InSession(mut session), Action::Star(speaker)) => {
+            if !session.star_latest(&speaker) {
+                return (State::InSession(session), vec![]);
+            }
+
+            (
+                State::InSession(session.clone()),
+                vec![Effect::SaveSession(session)],
+            )
+        }
+
+        (State::InSession(mut session), Action::SwitchMode(mode)) => {
+            let mode = mode.unwrap_or(match session.mode {
+                SessionMode::Morning => SessionMode::Evening,
+                SessionMode::Evening => SessionMode::Morning,
+            });
+            if mode == session.mode {
+                return (State::InSession(session), vec![]);
+            }
+            session.mode = mode;
+            session.add_entry(
+                Speaker::System,
+                format!("Switched to {} session", mode_name(mode)),
+            );
+
+            (
+                State::InSession(session.clone()),
+                vec![Effect::SaveSession(session)],
+            )
+        }
+
+        // Handled by the input loop; nothing in the session changes
+        (
+            State::InSession(session),
+            Action::Help | Action::ComposeInEditor { .. } | Action::UnknownCommand(_),
+        ) => (State::InSession(session), vec![]),
+
+        // Pausing - save the session as it is and leave it active in the index
+        (State::InSession(mut session), Action::Pause) => {
+            session.metadata.status = SessionStatus::Paused;
+            (
+                State::Paused(session.clone()),
+                vec![Effect::SaveSession(session)],
+            )
+        }
+
+        // Abandoning - keep the transcript but never write an entry
+        (State::InSession(mut session), Action::Abandon) => {
+            session.metadata.status = SessionStatus::Abandoned;
+            (
+                State::Abandoned(session.clone()),
+                vec![Effect::SaveSession(session), Effect::ClearIndex],
+            )
+        }
+
+        // Discarding - the user has already confirmed; delete the doc if one was saved
+        (State::InSession(session), Action::Discard) => {
+            let mut effects: Vec<_> = session
+                .metadata
+                .session_doc_id
+                .map(Effect::DeleteSession)
+                .into_iter()
+                .collect();
+            effects.push(Effect::ClearIndex);
+            (State::Discarded, effects)
+        }
+
+        // Stopping a private session - summarize locally instead of asking the AI
+        (State::InSession(mut session), Action::Stop) if session.metadata.private => {
+            session.mark_completed();
+            let analysis = stats::local_summary(&session);
+            (
+                State::AnalysisReady {
+                    session: session.clone(),
+                    analysis: analysis.clone(),
+                },
+                vec![
+                    Effect::SaveSession(session.clone()),
+                    Effect::CreateFinalEntry {
+                        session,
+                        analysis,
+                        analysis_status: AnalysisStatus::Local,
+                    },
+                ],
+            )
+        }
+
         // Stopping session (user pressed 's')
         (State::InSession(mut session), Action::Stop) => {
             session.mark_completed();
@@ -81,8 +337,21 @@ pub fn update(state: State, action: Action) -> (State, Vec<Effect>) {
         }
 
         // Analysis completed - show analysis and create final entry
-        (State::Analyzing(session), Action::AnalysisComplete(analysis)) => {
-            let entry_id = Uuid::new_v4();
+        (State::Analyzing(session), Action::AnalysisComplete(analysis)) => (
+            State::AnalysisReady {
+                session: session.clone(),
+                analysis: analysis.clone(),
+            },
+            vec![Effect::CreateFinalEntry {
+                session,
+                analysis,
+                analysis_status: AnalysisStatus::Complete,
+            }],
+        ),
+
+        // Analysis failed - save the entry now and queue the analysis to be filled in later
+        (State::Analyzing(session), Action::AnalysisFailed(error)) => {
+            let analysis = pending_analysis_notice(&error);
             (
                 State::AnalysisReady {
                     session: session.clone(),
@@ -90,45 +359,43 @@ pub fn update(state: State, action: Action) -> (State, Vec<Effect>) {
                 },
                 vec![Effect::CreateFinalEntry {
                     session,
-                    entry_id,
                     analysis,
+                    analysis_status: AnalysisStatus::Pending,
                 }],
             )
         }
 
-        // Final entry created successfully (legacy handler - should not be used anymore)
-        (State::Analyzing(_), Action::Stop) => {
-            let entry_id = Uuid::new_v4();
-            (
-                State::Done(WriteResult {
-                    entry_id,
-                    entry_path: format!("entry_{entry_id}.md"),
-                    session_completed: true,
-                }),
-                vec![Effect::ClearIndex],
-            )
-        }
-
-        // Final entry created successfully
-        (State::AnalysisReady { .. }, Action::FinalEntryCreated { entry_path, .. }) => {
-            let entry_id = Uuid::new_v4();
+        // Final entry created - archive the session doc with a link to the entry
+        (
+            State::AnalysisReady { mut session, .. },
+            Action::FinalEntryCreated {
+                entry_id,
+                entry_path,
+                analysis_status,
+                suggested_tags,
+                ..
+            },
+        ) => {
+            session.archive(entry_id);
             (
                 State::Done(WriteResult {
                     entry_id,
                     entry_path,
                     session_completed: true,
+                    analysis_status,
+                    suggested_tags,
                 }),
-                vec![Effect::ClearIndex],
+                vec![Effect::SaveSession(session), Effect::ClearIndex],
             )
         }
+
         // Session loaded successfully (from Resume)
-        (State::Initializing, Action::UserResponse(_)) => {
-            // This would happen after a successful session load
-            // The effect handler would have loaded the session and we transition to InSession
-            // This is a placeholder - the actual loaded session would be provided by the effect
+        (State::Initializing, Action::SessionLoaded(mut session)) => {
+            session.metadata.status = SessionStatus::Active;
+            session.add_entry(Speaker::System, "Session resumed".to_string());
             (
-                State::Error("Session load not implemented yet".to_string()),
-                vec![],
+                State::InSession(session.clone()),
+                vec![Effect::SaveSession(session)],
             )
         }
 
@@ -140,10 +407,29 @@ pub fn update(state: State, action: Action) -> (State, Vec<Effect>) {
     }
 }
 
+fn mode_name(mode: SessionMode) -> &'static str {
+    match mode {
+        SessionMode::Morning => "morning",
+        SessionMode::Evening => "evening",
+    }
+}
+
+/// Placeholder shown and stored while an entry's AI analysis is queued
+pub fn pending_analysis_notice(error: &str) -> String {
+    format!(
+        "**AI Analysis Pending**\n\n\
+        The AI analysis could not be generated right now. Your journal entry has been saved and \
+        the analysis will be added automatically the next time the journal starts, or when you \
+        run `journal analyze --pending`.\n\n\
+        Error details: {error}"
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state::SessionMode;
+    use crate::state::{ResurfacedEntry, RetractionReason, SessionMode, TranscriptSummary};
+    use uuid::Uuid;
 
     #[test]
     fn test_initial_start() {
@@ -190,6 +476,62 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_transcript_summarized() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::User, "First thing".to_string());
+        session.add_entry(Speaker::Coach, "Tell me more?".to_string());
+        session.add_entry(Speaker::User, "Latest thing".to_string());
+
+        let summary = TranscriptSummary {
+            summary: "They talked about the first thing.".to_string(),
+            entries_covered: 2,
+        };
+        let (new_state, effects) = update(
+            State::InSession(session),
+            Action::TranscriptSummarized(summary.clone()),
+        );
+
+        match new_state {
+            State::InSession(updated) => {
+                assert_eq!(updated.metadata.transcript_summary, Some(summary));
+            }
+            other => panic!("Expected InSession, got {other:?}"),
+        }
+        assert_eq!(effects.len(), 2);
+        assert!(matches!(effects[0], Effect::SaveSession(_)));
+        match &effects[1] {
+            Effect::RequestCoachResponse { user_response, .. } => {
+                assert_eq!(user_response, "Latest thing");
+            }
+            other => panic!("Expected RequestCoachResponse, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_session_saved_records_doc_id() {
+        let session_id = Uuid::new_v4();
+        let (new_state, effects) = update(
+            State::InSession(JournalSession::new(SessionMode::Morning)),
+            Action::SessionSaved(session_id),
+        );
+
+        assert_eq!(effects.len(), 0);
+        match new_state {
+            State::InSession(session) => {
+                assert_eq!(session.metadata.session_doc_id, Some(session_id));
+            }
+            other => panic!("Expected InSession, got {other:?}"),
+        }
+
+        // Also accepted while analysis is running, since saves complete asynchronously
+        let (new_state, _) = update(
+            State::Analyzing(JournalSession::new(SessionMode::Morning)),
+            Action::SessionSaved(session_id),
+        );
+        assert!(matches!(new_state, State::Analyzing(_)));
+    }
+
     #[test]
     fn test_stop_session() {
         let session = JournalSession::new(SessionMode::Morning);
@@ -203,14 +545,351 @@ mod tests {
         assert!(matches!(effects[1], Effect::GenerateAnalysis { .. }));
     }
 
+    fn private_session() -> JournalSession {
+        let mut session = JournalSession::new(SessionMode::Morning);
+        session.metadata.private = true;
+        session
+    }
+
+    #[test]
+    fn test_make_private() {
+        let session = JournalSession::new(SessionMode::Morning);
+        let (new_state, effects) = update(State::InSession(session), Action::MakePrivate);
+
+        match new_state {
+            State::InSession(session) => {
+                assert!(session.metadata.private);
+                assert_eq!(session.transcript.len(), 1);
+                assert!(matches!(session.transcript[0].speaker, Speaker::System));
+            }
+            other => panic!("Expected InSession, got {other:?}"),
+        }
+        assert_eq!(effects.len(), 1);
+        assert!(matches!(effects[0], Effect::SaveSession(_)));
+    }
+
+    #[test]
+    fn test_share_resurfaced() {
+        let resurfaced = vec![ResurfacedEntry {
+            entry_id: Uuid::new_v4(),
+            label: "One year ago today".to_string(),
+            date: chrono::NaiveDate::from_ymd_opt(2023, 3, 14).unwrap(),
+            snippet: "Started the new job".to_string(),
+        }];
+
+        let (new_state, effects) = update(
+            State::InSession(JournalSession::new(SessionMode::Evening)),
+            Action::ShareResurfaced(resurfaced.clone()),
+        );
+        let State::InSession(session) = new_state else {
+            panic!("Expected InSession");
+        };
+        assert_eq!(session.metadata.resurfaced, resurfaced);
+        assert_eq!(effects.len(), 1);
+
+        let (new_state, effects) = update(
+            State::InSession(private_session()),
+            Action::ShareResurfaced(resurfaced),
+        );
+        let State::InSession(session) = new_state else {
+            panic!("Expected InSession");
+        };
+        assert!(session.metadata.resurfaced.is_empty());
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn test_private_response_uses_offline_questions() {
+        let (new_state, effects) = update(
+            State::InSession(private_session()),
+            Action::UserResponse("Slept well".to_string()),
+        );
+
+        assert_eq!(effects.len(), 1);
+        assert!(matches!(effects[0], Effect::SaveSession(_)));
+        assert!(!effects
+            .iter()
+            .any(|e| matches!(e, Effect::RequestCoachResponse { .. })));
+
+        let State::InSession(session) = new_state else {
+            panic!("Expected InSession");
+        };
+        let first_question = SessionMode::Morning.get_initial_questions()[0];
+        assert_eq!(session.transcript.last().unwrap().content, first_question);
+
+        // Skipping moves on to the next question
+        let (new_state, _) = update(State::InSession(session), Action::NextQuestion);
+        let State::InSession(session) = new_state else {
+            panic!("Expected InSession");
+        };
+        let second_question = SessionMode::Morning.get_initial_questions()[1];
+        assert_eq!(session.transcript.last().unwrap().content, second_question);
+    }
+
+    #[test]
+    fn test_private_stop_skips_ai_analysis() {
+        let mut session = private_session();
+        session.add_entry(Speaker::User, "Quiet morning".to_string());
+
+        let (new_state, effects) = update(State::InSession(session), Action::Stop);
+
+        match new_state {
+            State::AnalysisReady { analysis, .. } => {
+                assert!(analysis.contains("local only"));
+            }
+            other => panic!("Expected AnalysisReady, got {other:?}"),
+        }
+        assert_eq!(effects.len(), 2);
+        assert!(matches!(effects[0], Effect::SaveSession(_)));
+        assert!(matches!(effects[1], Effect::CreateFinalEntry { .. }));
+    }
+
+    #[test]
+    fn test_analysis_failed_queues_pending_entry() {
+        let (new_state, effects) = update(
+            State::Analyzing(JournalSession::new(SessionMode::Evening)),
+            Action::AnalysisFailed("backend offline".to_string()),
+        );
+
+        match new_state {
+            State::AnalysisReady { analysis, .. } => {
+                assert!(analysis.contains("AI Analysis Pending"));
+                assert!(analysis.contains("backend offline"));
+            }
+            other => panic!("Expected AnalysisReady, got {other:?}"),
+        }
+        assert_eq!(effects.len(), 1);
+        assert!(matches!(
+            effects[0],
+            Effect::CreateFinalEntry {
+                analysis_status: AnalysisStatus::Pending,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_undo_retracts_response_and_reply() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::Coach, "How was your day?".to_string());
+        session.add_entry(Speaker::User, "Long".to_string());
+        session.add_entry(Speaker::Coach, "What made it long?".to_string());
+
+        let (new_state, effects) = update(State::InSession(session), Action::Undo);
+        match &new_state {
+            State::InSession(session) => {
+                assert_eq!(session.transcript.len(), 1);
+                assert_eq!(session.transcript[0].content, "How was your day?");
+            }
+            other => panic!("Expected InSession, got {other:?}"),
+        }
+        assert!(matches!(effects[..], [Effect::SaveSession(_)]));
+        if let State::InSession(session) = &new_state {
+            let undone = &session.metadata.retracted;
+            assert_eq!(undone.len(), 2);
+            assert!(undone
+                .iter()
+                .all(|retracted| retracted.reason == RetractionReason::Undone));
+        }
+
+        // Nothing left to undo
+        let (state, effects) = update(new_state, Action::Undo);
+        assert!(matches!(state, State::InSession(_)));
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn test_retry_requests_new_coach_reply() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::User, "Long day".to_string());
+        session.add_entry(Speaker::Coach, "An unhelpful reply".to_string());
+
+        let (new_state, effects) = update(State::InSession(session.clone()), Action::Retry);
+        match &new_state {
+            State::InSession(session) => assert_eq!(session.transcript.len(), 1),
+            other => panic!("Expected InSession, got {other:?}"),
+        }
+        assert!(matches!(
+            &effects[1],
+            Effect::RequestCoachResponse { user_response, .. } if user_response == "Long day"
+        ));
+
+        // Private sessions have no coach to ask
+        session.metadata.private = true;
+        let (state, effects) = update(State::InSession(session), Action::Retry);
+        assert!(matches!(state, State::InSession(s) if s.transcript.len() == 2));
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn test_revise_keeps_history() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.add_entry(Speaker::User, "Bad day".to_string());
+        session.add_entry(Speaker::Coach, "Sorry to hear that".to_string());
+        let id = session.transcript[0].id;
+
+        let (new_state, effects) = update(
+            State::InSession(session),
+            Action::ReviseLast("Mixed day".to_string()),
+        );
+        let State::InSession(session) = new_state else {
+            panic!("Expected InSession");
+        };
+
+        assert_eq!(session.transcript.len(), 1);
+        assert_eq!(session.transcript[0].id, id);
+        assert_eq!(session.transcript[0].content, "Mixed day");
+        assert_eq!(session.transcript[0].revisions[0].content, "Bad day");
+        assert_eq!(session.metadata.retracted.len(), 1);
+        assert_eq!(
+            session.metadata.retracted[0].reason,
+            RetractionReason::Regenerated
+        );
+        assert!(matches!(
+            &effects[1],
+            Effect::RequestCoachResponse { user_response, .. } if user_response == "Mixed day"
+        ));
+    }
+
+    #[test]
+    fn test_session_commands() {
+        let mut session = JournalSession::new(SessionMode::Morning);
+        session.add_entry(Speaker::User, "Worth remembering".to_string());
+        let state = State::InSession(session);
+
+        let (state, _) = update(
+            state,
+            Action::Tag(vec!["work".to_string(), "work".to_string()]),
+        );
+        let (state, _) = update(state, Action::Star(Speaker::User));
+        let (state, _) = update(state, Action::SwitchMode(None));
+        let (state, effects) = update(state, Action::Skip);
+
+        let State::InSession(session) = state else {
+            panic!("Expected InSession");
+        };
+        assert_eq!(session.metadata.tags, vec!["work"]);
+        assert!(session.transcript[0].starred);
+        assert_eq!(session.mode, SessionMode::Evening);
+        let skipped_to = &session.transcript.last().unwrap().content;
+        assert_eq!(
+            Some(skipped_to.as_str()),
+            SessionMode::Evening
+                .get_initial_questions()
+                .first()
+                .copied()
+        );
+        assert_eq!(effects.len(), 1);
+
+        let (state, effects) = update(State::InSession(session), Action::Help);
+        assert!(matches!(state, State::InSession(_)));
+        assert!(effects.is_empty());
+    }
+
+    #[test]
+    fn test_pause_saves_session() {
+        let (new_state, effects) = update(
+            State::InSession(JournalSession::new(SessionMode::Morning)),
+            Action::Pause,
+        );
+
+        assert!(matches!(
+            &new_state,
+            State::Paused(session) if session.metadata.status == SessionStatus::Paused
+        ));
+        assert!(new_state.is_terminal());
+        assert!(matches!(effects[..], [Effect::SaveSession(_)]));
+    }
+
+    #[test]
+    fn test_abandon_and_discard() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.metadata.session_doc_id = Some(Uuid::new_v4());
+
+        let (state, effects) = update(State::InSession(session.clone()), Action::Abandon);
+        assert!(matches!(
+            &state,
+            State::Abandoned(session) if session.metadata.status == SessionStatus::Abandoned
+        ));
+        assert!(matches!(
+            effects[..],
+            [Effect::SaveSession(_), Effect::ClearIndex]
+        ));
+
+        let (state, effects) = update(State::InSession(session.clone()), Action::Discard);
+        assert_eq!(state, State::Discarded);
+        assert!(matches!(
+            effects[..],
+            [Effect::DeleteSession(id), Effect::ClearIndex]
+                if Some(id) == session.metadata.session_doc_id
+        ));
+    }
+
+    #[test]
+    fn test_session_loaded_resumes() {
+        let mut session = JournalSession::new(SessionMode::Evening);
+        session.metadata.status = SessionStatus::Paused;
+
+        let (state, effects) = update(State::Initializing, Action::SessionLoaded(session));
+        match state {
+            State::InSession(session) => {
+                assert_eq!(session.metadata.status, SessionStatus::Active);
+                assert_eq!(session.transcript.last().unwrap().speaker, Speaker::System);
+            }
+            other => panic!("Expected InSession, got {other:?}"),
+        }
+        assert!(matches!(effects[..], [Effect::SaveSession(_)]));
+    }
+
+    #[test]
+    fn test_final_entry_created_archives_session() {
+        let entry_id = Uuid::new_v4();
+        let entry_path = std::path::PathBuf::from(format!("/vault/docs/{entry_id}.md"));
+
+        let (new_state, effects) = update(
+            State::AnalysisReady {
+                session: JournalSession::new(SessionMode::Evening),
+                analysis: "Analysis".to_string(),
+            },
+            Action::FinalEntryCreated {
+                entry_id,
+                entry_path: entry_path.clone(),
+                analysis: "Analysis".to_string(),
+                analysis_status: AnalysisStatus::Complete,
+                suggested_tags: vec!["garden".to_string()],
+            },
+        );
+
+        assert_eq!(
+            new_state,
+            State::Done(WriteResult {
+                entry_id,
+                entry_path,
+                session_completed: true,
+                analysis_status: AnalysisStatus::Complete,
+                suggested_tags: vec!["garden".to_string()],
+            })
+        );
+        match &effects[..] {
+            [Effect::SaveSession(session), Effect::ClearIndex] => {
+                assert_eq!(session.metadata.status, SessionStatus::Archived);
+                assert_eq!(session.metadata.final_entry_id, Some(entry_id));
+                assert!(session.metadata.archived_at.is_some());
+            }
+            other => panic!("Expected SaveSession and ClearIndex, got {other:?}"),
+        }
+    }
+
     #[test]
     fn test_invalid_transitions() {
         // Test invalid action for state
         let (new_state, effects) = update(
             State::Done(WriteResult {
                 entry_id: Uuid::new_v4(),
-                entry_path: "test".to_string(),
+                entry_path: "test".into(),
                 session_completed: true,
+                analysis_status: AnalysisStatus::Complete,
+                suggested_tags: Vec::new(),
             }),
             Action::UserResponse("test".to_string()),
         );
@@ -0,0 +1,311 @@
+use crate::doctor;
+use crate::entries::{self, ENTRY_DOC_TYPE};
+use crate::pack::{self, PackVersion};
+use crate::sessions::SESSION_DOC_TYPE;
+use aethel_core::{Patch, PatchMode};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+/// Where docs are copied before an upgrade rewrites them, relative to the vault root
+pub const BACKUPS_DIR: &str = ".aethel/backups";
+
+/// A rewrite of existing docs' frontmatter that ships with a pack version
+pub struct Migration {
+    /// The pack version whose schemas expect the change
+    pub version: PackVersion,
+    pub doc_type: &'static str,
+    pub description: &'static str,
+    /// Changes the frontmatter in place, returning whether anything changed. Running it again
+    /// on migrated frontmatter must change nothing.
+    pub migrate: fn(&mut Map<String, Value>) -> bool,
+}
+
+/// Every migration, oldest first
+pub static MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: PackVersion::new(0, 2, 0),
+        doc_type: ENTRY_DOC_TYPE,
+        description: "Add numeric mood and energy scores to entries",
+        migrate: add_scores,
+    },
+    Migration {
+        version: PackVersion::new(0, 2, 0),
+        doc_type: ENTRY_DOC_TYPE,
+        description: "Record that older entries hold an AI analysis",
+        migrate: add_analysis_status,
+    },
+    Migration {
+        version: PackVersion::new(0, 2, 0),
+        doc_type: SESSION_DOC_TYPE,
+        description: "Record whether older sessions were completed",
+        migrate: add_session_status,
+    },
+];
+
+/// The changes an upgrade would make to one doc
+#[derive(Debug, Clone, PartialEq)]
+pub struct DocChange {
+    pub doc_id: Uuid,
+    /// Descriptions of the migrations that change the doc
+    pub migrations: Vec<&'static str>,
+    /// The doc's frontmatter after every migration has run
+    pub frontmatter: Map<String, Value>,
+}
+
+/// What `journal upgrade` would do to the vault
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpgradePlan {
+    /// The newest pack installed, or `None` for a vault that has never had one
+    pub from: Option<PackVersion>,
+    pub to: PackVersion,
+    pub changes: Vec<DocChange>,
+}
+
+impl UpgradePlan {
+    /// Whether the vault already has the current pack and nothing to migrate
+    pub fn is_empty(&self) -> bool {
+        self.from == Some(self.to) && self.changes.is_empty()
+    }
+}
+
+/// Work out which migrations apply to the vault and what they'd change, without writing
+pub fn plan(vault_path: &Path) -> Result<UpgradePlan> {
+    let from = pack::installed_version(vault_path);
+    let to = pack::current_version();
+    let migrations: Vec<&Migration> = MIGRATIONS
+        .iter()
+        .filter(|migration| from.is_none_or(|from| migration.version > from))
+        .filter(|migration| migration.version <= to)
+        .collect();
+
+    let mut changes = Vec::new();
+    if !migrations.is_empty() {
+        for doc in doctor::read_docs(vault_path)? {
+            let mut frontmatter = doc.frontmatter_extra;
+            let applied: Vec<&'static str> = migrations
+                .iter()
+                .filter(|migration| migration.doc_type == doc.doc_type)
+                .filter(|migration| (migration.migrate)(&mut frontmatter))
+                .map(|migration| migration.description)
+                .collect();
+            if !applied.is_empty() {
+                changes.push(DocChange {
+                    doc_id: doc.uuid,
+                    migrations: applied,
+                    frontmatter,
+                });
+            }
+        }
+    }
+
+    Ok(UpgradePlan { from, to, changes })
+}
+
+/// Carry out a plan: back up the docs it changes, rewrite their frontmatter, then install the
+/// current pack alongside the old one. Returns the backup directory if any docs were changed.
+pub fn apply(vault_path: &Path, plan: &UpgradePlan) -> Result<Option<PathBuf>> {
+    let backup_dir = match plan.changes.is_empty() {
+        true => None,
+        false => Some(back_up(vault_path, plan)?),
+    };
+
+    for change in &plan.changes {
+        pack::apply_checked(
+            vault_path,
+            Patch {
+                uuid: Some(change.doc_id),
+                doc_type: None,
+                mode: PatchMode::MergeFrontmatter,
+                frontmatter: Some(Value::Object(change.frontmatter.clone())),
+                body: None,
+            },
+        )
+        .with_context(|| format!("Failed to migrate {}", change.doc_id))?;
+    }
+
+    pack::install(vault_path)?;
+    Ok(backup_dir)
+}
+
+/// Copy every doc the plan changes into a fresh backup directory
+fn back_up(vault_path: &Path, plan: &UpgradePlan) -> Result<PathBuf> {
+    let from = plan
+        .from
+        .map_or_else(|| "none".to_string(), |from| from.to_string());
+    let backup_dir = vault_path.join(BACKUPS_DIR).join(format!(
+        "journal@{from}-{}",
+        Utc::now().format("%Y%m%dT%H%M%SZ")
+    ));
+    fs::create_dir_all(&backup_dir).context("Failed to create backup directory")?;
+
+    for change in &plan.changes {
+        let file_name = format!("{}.md", change.doc_id);
+        fs::copy(
+            vault_path.join("docs").join(&file_name),
+            backup_dir.join(&file_name),
+        )
+        .with_context(|| format!("Failed to back up {}", change.doc_id))?;
+    }
+    Ok(backup_dir)
+}
+
+/// 0.2.0: numeric `mood_score` and `energy_score` alongside the mood and energy labels
+fn add_scores(frontmatter: &mut Map<String, Value>) -> bool {
+    let mood = add_score(frontmatter, "mood", entries::mood_value);
+    let energy = add_score(frontmatter, "energy", entries::energy_value);
+    mood || energy
+}
+
+/// Add `<label>_score` for the label's value, unless it's already there
+fn add_score(
+    frontmatter: &mut Map<String, Value>,
+    label: &str,
+    score: fn(&str) -> Option<f64>,
+) -> bool {
+    let key = format!("{label}_score");
+    if frontmatter.contains_key(&key) {
+        return false;
+    }
+    let value = frontmatter
+        .get(label)
+        .and_then(Value::as_str)
+        .and_then(score);
+    frontmatter.insert(key, value.into());
+    true
+}
+
+/// 0.2.0: `analysis_status` and `ai_processed` on entries. Every entry written before them
+/// was analysed by the AI backend.
+fn add_analysis_status(frontmatter: &mut Map<String, Value>) -> bool {
+    if frontmatter.contains_key("analysis_status") {
+        return false;
+    }
+    frontmatter.insert("analysis_status".to_string(), json!("complete"));
+    frontmatter
+        .entry("ai_processed")
+        .or_insert(Value::Bool(true));
+    true
+}
+
+/// 0.2.0: `status` in session metadata, which older sessions only hint at with
+/// `final_entry_id` and `completed_at`
+fn add_session_status(frontmatter: &mut Map<String, Value>) -> bool {
+    let Some(metadata) = frontmatter
+        .entry("metadata")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+    else {
+        return false;
+    };
+    if metadata.contains_key("status") {
+        return false;
+    }
+    let is_set = |key: &str| metadata.get(key).is_some_and(|value| !value.is_null());
+    let status = if is_set("final_entry_id") {
+        "archived"
+    } else if is_set("completed_at") {
+        "completed"
+    } else {
+        "active"
+    };
+    metadata.insert("status".to_string(), json!(status));
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aethel_core::{apply_patch, read_doc};
+    use tempfile::TempDir;
+
+    fn create_entry(vault_path: &Path, mood: &str) -> Uuid {
+        apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(ENTRY_DOC_TYPE.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(json!({
+                    "session_id": null,
+                    "mode": "Evening",
+                    "session_type": "evening",
+                    "title": "Evening Journal Entry",
+                    "mood": mood,
+                })),
+                body: Some("# Entry".to_string()),
+            },
+        )
+        .unwrap()
+        .uuid
+    }
+
+    #[test]
+    fn test_upgrade_migrates_docs_and_installs_pack() {
+        let temp_dir = TempDir::new().unwrap();
+        let vault_path = temp_dir.path();
+        let old_pack = vault_path.join(pack::pack_dir(PackVersion::new(0, 1, 0)));
+        fs::create_dir_all(&old_pack).unwrap();
+        fs::write(old_pack.join("pack.json"), "{}").unwrap();
+        let entry_id = create_entry(vault_path, "positive");
+        let session_id = apply_patch(
+            vault_path,
+            Patch {
+                uuid: None,
+                doc_type: Some(SESSION_DOC_TYPE.to_string()),
+                mode: PatchMode::Create,
+                frontmatter: Some(json!({
+                    "mode": "Evening",
+                    "session_type": "evening",
+                    "metadata": { "completed_at": "2025-01-01T20:00:00Z" },
+                })),
+                body: Some("[]".to_string()),
+            },
+        )
+        .unwrap()
+        .uuid;
+
+        let upgrade = plan(vault_path).unwrap();
+        assert_eq!(upgrade.from, Some(PackVersion::new(0, 1, 0)));
+        assert_eq!(upgrade.changes.len(), 2);
+        let entry = upgrade
+            .changes
+            .iter()
+            .find(|change| change.doc_id == entry_id)
+            .unwrap();
+        assert_eq!(entry.migrations.len(), 2);
+        assert_eq!(entry.frontmatter["mood_score"], json!(3.0));
+        assert_eq!(entry.frontmatter["energy_score"], Value::Null);
+        assert_eq!(entry.frontmatter["analysis_status"], json!("complete"));
+        assert_eq!(entry.frontmatter["ai_processed"], json!(true));
+        let session = upgrade
+            .changes
+            .iter()
+            .find(|change| change.doc_id == session_id)
+            .unwrap();
+        assert_eq!(
+            session.frontmatter["metadata"]["status"],
+            json!("completed")
+        );
+
+        // Planning is a dry run
+        let doc = read_doc(vault_path, &entry_id).unwrap();
+        assert!(!doc.frontmatter_extra.contains_key("mood_score"));
+
+        let backup_dir = apply(vault_path, &upgrade).unwrap().unwrap();
+        let backup = fs::read_to_string(backup_dir.join(format!("{entry_id}.md"))).unwrap();
+        assert!(!backup.contains("mood_score"));
+        let doc = read_doc(vault_path, &entry_id).unwrap();
+        assert_eq!(doc.frontmatter_extra["mood_score"], json!(3.0));
+
+        assert_eq!(
+            pack::installed_version(vault_path),
+            Some(pack::current_version())
+        );
+        assert!(old_pack.join("pack.json").exists());
+        assert!(plan(vault_path).unwrap().is_empty());
+    }
+}
@@ -1,4 +1,6 @@
-use crate::state::{JournalSession, Speaker, State, WriteResult};
+use crate::action::{Action, COMMANDS};
+use crate::state::{AnalysisStatus, JournalSession, ResurfacedEntry, Speaker, State, WriteResult};
+use crate::streak::StreakStatus;
 
 /// Main view function that renders the current state
 pub fn view(state: &State) {
@@ -7,11 +9,11 @@ pub fn view(state: &State) {
         State::PromptingForNew => render_prompting_for_new(),
         State::InSession(session) => render_in_session(session),
         State::Analyzing(session) => render_analyzing(session),
-        State::AnalysisReady {
-            session: _,
-            analysis,
-        } => render_analysis_ready(analysis),
+        State::AnalysisReady { session, analysis } => render_analysis_ready(session, analysis),
         State::Done(result) => render_done(result),
+        State::Paused(_) => render_paused(),
+        State::Abandoned(_) => render_abandoned(),
+        State::Discarded => render_discarded(),
         State::Error(msg) => render_error(msg),
     }
 }
@@ -41,12 +43,17 @@ fn render_in_session(session: &JournalSession) {
                 // For coach messages, check if it's a question or response
                 if latest.content.ends_with('?') {
                     println!("\n💭 {}", latest.content);
+                    if session.get_user_responses().is_empty() {
+                        println!(
+                            "   (Finish with a blank line or Ctrl-D. Type /help for commands.)"
+                        );
+                    }
                     print!("\n> ");
                     use std::io::{self, Write};
                     io::stdout().flush().unwrap();
                 } else {
                     println!("\n🧘 Coach: {}", latest.content);
-                    println!("\n⏸️  Press (s)top to end session or continue sharing...");
+                    println!("\n⏸️  Type /stop to end the session, or continue sharing...");
                 }
             }
             Speaker::User => {
@@ -59,14 +66,67 @@ fn render_in_session(session: &JournalSession) {
     }
 }
 
+/// Render snippets of past entries from this day, shown before the first question
+pub fn render_resurfaced(resurfaced: &[ResurfacedEntry]) {
+    if resurfaced.is_empty() {
+        return;
+    }
+
+    println!("\n📅 On this day");
+    for entry in resurfaced {
+        println!("  • {} ({}): {}", entry.label, entry.date, entry.snippet);
+    }
+}
+
+/// Render the one-line streak and goal status, if there is anything to show yet
+pub fn render_streaks(status: &StreakStatus) {
+    if let Some(summary) = status.summary() {
+        println!("\n{summary}");
+    }
+}
+
+/// Render the slash commands available during a session
+pub fn render_help() {
+    println!("\n📖 Commands");
+    for (command, description) in COMMANDS {
+        println!("  {command:<26}{description}");
+    }
+}
+
+/// The slash commands on a single line, for narrow spaces
+pub fn command_names() -> String {
+    COMMANDS
+        .iter()
+        .map(|(command, _)| command.split(' ').next().unwrap_or(command))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Short confirmation for commands whose effect isn't otherwise visible
+pub fn acknowledgement(action: &Action) -> Option<String> {
+    match action {
+        Action::Tag(tags) => Some(format!("🏷️  Tagged: {}", tags.join(", "))),
+        Action::Star(Speaker::Coach) => Some("⭐ Starred the coach's last line".to_string()),
+        Action::Star(_) => Some("⭐ Starred your last response".to_string()),
+        Action::UnknownCommand(command) => Some(format!(
+            "❓ Unknown command '{command}'. Type /help to see what's available."
+        )),
+        _ => None,
+    }
+}
+
 /// Render the analyzing state
 fn render_analyzing(_session: &JournalSession) {
     println!("\n🔍 Analyzing your session...");
 }
 
 /// Render the analysis ready state
-fn render_analysis_ready(analysis: &str) {
-    println!("\n🧠 **AI Analysis of Your Session**");
+fn render_analysis_ready(session: &JournalSession, analysis: &str) {
+    if session.metadata.private {
+        println!("\n🔒 **Your Private Session**");
+    } else {
+        println!("\n🧠 **AI Analysis of Your Session**");
+    }
     println!("{}", "=".repeat(50));
     println!("{analysis}");
     println!("{}", "=".repeat(50));
@@ -76,10 +136,65 @@ fn render_analysis_ready(analysis: &str) {
 fn render_done(result: &WriteResult) {
     println!("\n✨ **Session Complete!**");
     println!(
-        "📝 Your journal entry has been saved to: {}",
-        result.entry_path
+        "📝 Your journal entry {} has been saved to:",
+        result.entry_id
     );
-    println!("🔍 The AI analysis has been included in your entry for future reference.");
+    println!("   {}", result.entry_path.display());
+    match result.analysis_status {
+        AnalysisStatus::Complete => {
+            println!("🔍 The AI analysis has been included in your entry for future reference.")
+        }
+        AnalysisStatus::Pending => println!(
+            "⏳ The AI analysis is queued and will be added the next time the journal starts."
+        ),
+        AnalysisStatus::Local => {
+            println!("🔒 Your entry has a local summary only. Nothing was sent to an AI backend.")
+        }
+    }
+    if !result.suggested_tags.is_empty() {
+        println!(
+            "🏷️  Suggested tags: {}. Add them with `journal tags add {} {}`",
+            result.suggested_tags.join(", "),
+            result.entry_id,
+            result.suggested_tags.join(" ")
+        );
+    }
+}
+
+/// Render the paused state
+fn render_paused() {
+    println!("\n⏸️  Session paused. Everything so far has been saved.");
+    println!("Run `journal resume` to pick it up again.");
+}
+
+/// Render the abandoned state
+fn render_abandoned() {
+    println!("\n🚪 Session abandoned. No journal entry was written; the transcript is kept.");
+}
+
+/// Render the discarded state
+fn render_discarded() {
+    println!("\n🗑️  Session discarded and deleted.");
+}
+
+/// Render the last few lines of a resumed session as a reminder of where it left off
+pub fn render_recap(session: &JournalSession) {
+    let recent: Vec<_> = session
+        .transcript
+        .iter()
+        .filter(|entry| entry.speaker != Speaker::System)
+        .collect();
+    println!("\n↩️  Picking up where you left off:");
+    for entry in recent.iter().skip(recent.len().saturating_sub(3)) {
+        let speaker = match entry.speaker {
+            Speaker::User => "You",
+            _ => "Coach",
+        };
+        println!("  {speaker}: {}", entry.content);
+    }
+    print!("\n> ");
+    use std::io::{self, Write};
+    io::stdout().flush().unwrap();
 }
 
 /// Render error messages
@@ -90,7 +205,7 @@ fn render_error(msg: &str) {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::state::{SessionMetadata, SessionMode, TranscriptEntry};
+    use crate::state::{SessionMetadata, SessionMode, SessionStatus, TranscriptEntry};
     use chrono::Utc;
     use uuid::Uuid;
 
@@ -108,15 +223,25 @@ mod tests {
         let session = JournalSession {
             mode: SessionMode::Morning,
             transcript: vec![TranscriptEntry {
+                id: Uuid::new_v4(),
                 timestamp: Utc::now(),
                 speaker: Speaker::Coach,
                 content: "What are your intentions for today?".to_string(),
+                starred: false,
+                revisions: Vec::new(),
             }],
             metadata: SessionMetadata {
                 session_doc_id: Some(Uuid::new_v4()),
+                status: SessionStatus::Active,
                 final_entry_id: None,
                 completed_at: None,
+                archived_at: None,
                 custom_fields: std::collections::HashMap::new(),
+                transcript_summary: None,
+                private: false,
+                resurfaced: Vec::new(),
+                tags: Vec::new(),
+                retracted: Vec::new(),
             },
         };
         view(&State::InSession(session.clone()));
@@ -126,16 +251,29 @@ mod tests {
 
         // AnalysisReady
         view(&State::AnalysisReady {
-            session,
+            session: session.clone(),
             analysis: "Great session!".to_string(),
         });
 
-        // Done
-        view(&State::Done(WriteResult {
-            entry_id: Uuid::new_v4(),
-            entry_path: "/tmp/test/entry.md".to_string(),
-            session_completed: true,
-        }));
+        // Paused, abandoned and discarded
+        view(&State::Paused(session.clone()));
+        view(&State::Abandoned(session));
+        view(&State::Discarded);
+
+        // Done, with each kind of analysis
+        for analysis_status in [
+            AnalysisStatus::Complete,
+            AnalysisStatus::Pending,
+            AnalysisStatus::Local,
+        ] {
+            view(&State::Done(WriteResult {
+                entry_id: Uuid::new_v4(),
+                entry_path: "/tmp/test/entry.md".into(),
+                session_completed: true,
+                analysis_status,
+                suggested_tags: vec!["work".to_string()],
+            }));
+        }
 
         // Error
         view(&State::Error("Test error".to_string()));
@@ -148,33 +286,49 @@ mod tests {
             transcript: vec![],
             metadata: SessionMetadata {
                 session_doc_id: Some(Uuid::new_v4()),
+                status: SessionStatus::Active,
                 final_entry_id: None,
                 completed_at: None,
+                archived_at: None,
                 custom_fields: std::collections::HashMap::new(),
+                transcript_summary: None,
+                private: false,
+                resurfaced: Vec::new(),
+                tags: Vec::new(),
+                retracted: Vec::new(),
             },
         };
 
         // Test coach question
         session.transcript.push(TranscriptEntry {
+            id: Uuid::new_v4(),
             timestamp: Utc::now(),
             speaker: Speaker::Coach,
             content: "How was your day?".to_string(),
+            starred: false,
+            revisions: Vec::new(),
         });
         render_in_session(&session);
 
         // Test coach response (non-question)
         session.transcript.push(TranscriptEntry {
+            id: Uuid::new_v4(),
             timestamp: Utc::now(),
             speaker: Speaker::Coach,
             content: "That sounds wonderful.".to_string(),
+            starred: false,
+            revisions: Vec::new(),
         });
         render_in_session(&session);
 
         // Test system message
         session.transcript.push(TranscriptEntry {
+            id: Uuid::new_v4(),
             timestamp: Utc::now(),
             speaker: Speaker::System,
             content: "Session saved.".to_string(),
+            starred: false,
+            revisions: Vec::new(),
         });
         render_in_session(&session);
     }